@@ -0,0 +1,116 @@
+use std::fmt::Display;
+
+use crate::datetime::Timestamp;
+use crate::json_crawl::Hit;
+use crate::rules::PathPattern;
+
+/// Where an anchor's time comes from.
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub enum AnchorSource {
+    Fixed(Timestamp),
+    /// The earliest timestamp at a path matching the pattern, so the anchor follows the document.
+    Path(PathPattern),
+    /// The current time.
+    Now,
+    EarliestHit,
+    LatestHit,
+}
+
+impl Display for AnchorSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnchorSource::Fixed(ts) => write!(f, "{}", ts),
+            AnchorSource::Path(pattern) => write!(f, "{}", pattern),
+            AnchorSource::Now => write!(f, "now"),
+            AnchorSource::EarliestHit => write!(f, "earliest hit"),
+            AnchorSource::LatestHit => write!(f, "latest hit"),
+        }
+    }
+}
+
+/// A named point in time that timestamps are shown relative to.
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Anchor {
+    pub name: String,
+    pub source: AnchorSource,
+}
+
+impl Anchor {
+    pub fn new(name: &str, source: AnchorSource) -> Self {
+        Anchor {
+            name: name.to_owned(),
+            source,
+        }
+    }
+
+    /// The anchor's time, given the hits shown. `None` if it has no time, e.g. if no hit matches
+    /// its path.
+    pub fn resolve(&self, hits: &[Hit], now: Timestamp) -> Option<Timestamp> {
+        let times = hits.iter().map(|hit| hit.ts);
+        match &self.source {
+            AnchorSource::Fixed(ts) => Some(*ts),
+            AnchorSource::Path(pattern) => hits
+                .iter()
+                .filter(|hit| pattern.matches(&hit.path))
+                .map(|hit| hit.ts)
+                .min(),
+            AnchorSource::Now => Some(now),
+            AnchorSource::EarliestHit => times.min(),
+            AnchorSource::LatestHit => times.max(),
+        }
+    }
+}
+
+/// Each anchor's name and time (see `Anchor::resolve`).
+pub fn resolve_anchors(
+    anchors: &[Anchor],
+    hits: &[Hit],
+    now: Timestamp,
+) -> Vec<(String, Option<Timestamp>)> {
+    anchors
+        .iter()
+        .map(|anchor| (anchor.name.clone(), anchor.resolve(hits, now)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+
+    #[test]
+    fn test_resolve() {
+        let hits = Scanner::new()
+            .years(2020, 2030)
+            .scan_str(r#"{"start": 1692694500, "events": [1692694400, 1692694600]}"#)
+            .unwrap();
+        let now = Timestamp::from_secs(1700000000);
+        let resolve = |source: AnchorSource| Anchor::new("a", source).resolve(&hits, now);
+        let fixed = Timestamp::new(5, 500_000_000).unwrap();
+        assert_eq!(resolve(AnchorSource::Fixed(fixed)), Some(fixed));
+        let json = serde_json::to_string(&AnchorSource::Fixed(fixed)).unwrap();
+        assert_eq!(
+            serde_json::from_str(&json).ok(),
+            Some(AnchorSource::Fixed(fixed))
+        );
+        assert!(serde_json::from_str::<AnchorSource>(r#"{"Fixed": [5, 1000000000]}"#).is_err());
+        assert_eq!(
+            resolve(AnchorSource::Path("start".parse().unwrap())),
+            Some(Timestamp::from_secs(1692694500))
+        );
+        assert_eq!(
+            resolve(AnchorSource::Path("events[*]".parse().unwrap())),
+            Some(Timestamp::from_secs(1692694400))
+        );
+        assert_eq!(resolve(AnchorSource::Path("end".parse().unwrap())), None);
+        assert_eq!(resolve(AnchorSource::Now), Some(now));
+        assert_eq!(
+            resolve(AnchorSource::LatestHit),
+            Some(Timestamp::from_secs(1692694600))
+        );
+        assert_eq!(
+            Anchor::new("a", AnchorSource::EarliestHit).resolve(&[], now),
+            None
+        );
+    }
+}
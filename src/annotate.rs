@@ -0,0 +1,43 @@
+use serde_json::{json, Value};
+
+use crate::datetime::{timestamp_to_str, Tz};
+use crate::json_crawl::Hit;
+
+/// A copy of `value` where every hit is replaced by an object holding the original value,
+/// its human readable time and how it was read, e.g.
+/// `{"value": 1692694500, "time": "2023-08-22 08:55:00", "interpretation": "Unix seconds"}`.
+pub fn annotate(value: &Value, hits: &[Hit], fmt: &str, tz: Tz) -> Value {
+    let mut annotated = value.clone();
+    for hit in hits {
+        if let Some(target) = hit.path.get_mut(&mut annotated) {
+            let original = target.take();
+            *target = json!({
+                "value": original,
+                "time": timestamp_to_str(hit.ts, fmt, tz),
+                "interpretation": hit.interpretation.to_string(),
+            });
+        }
+    }
+    annotated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+
+    #[test]
+    fn test_annotate() {
+        let value =
+            json!({"id": 7, "events": [{"at": 1692694500}, {"at": "2023-08-22T08:55:00Z"}]});
+        let hits = Scanner::new().years(2020, 2030).scan(&value);
+        let annotated = annotate(&value, &hits, "%Y-%m-%d %H:%M", Tz::Utc);
+        assert_eq!(
+            annotated,
+            json!({"id": 7, "events": [
+                {"at": {"value": 1692694500, "time": "2023-08-22 08:55", "interpretation": "Unix seconds"}},
+                {"at": {"value": "2023-08-22T08:55:00Z", "time": "2023-08-22 08:55", "interpretation": "Format rfc3339"}},
+            ]})
+        );
+    }
+}
@@ -1,13 +1,27 @@
-use crate::datetime::ts_to_str;
-use crate::json_crawl::{crawl_json, JsonPathPart};
-use crate::tree::tree;
-use crate::{datetime::year_to_ts, json_crawl::JsonPath};
-use chrono::{Datelike, Utc};
-use egui::{vec2, Response, ScrollArea, Ui};
-
-/// We derive Deserialize/Serialize so we can persist app state on shutdown.
-// #[derive(serde::Deserialize, serde::Serialize)]
-// #[serde(default)] // if we add new fields, give them default values when deserializing old state
+use crate::anchors::{resolve_anchors, Anchor, AnchorSource};
+use crate::annotate::annotate;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::clipboard::ClipboardMonitor;
+use crate::datetime::{
+    parse_date, parse_duration, timestamp_to_str, DurationStyle, Timestamp, Tz, DURATION_STYLES,
+    EPOCH_FAMILIES,
+};
+use crate::documents::{Document, Documents, Tab};
+use crate::editor;
+use crate::expiry::ExpiryFilter;
+use crate::export::hits_to_csv;
+use crate::files::{FileDialogs, OpenedFile};
+use crate::gaps::{deltas, interval_stats, IntervalStats};
+use crate::json_crawl::{Hit, Interpretation, JsonPath};
+use crate::json_text::{format_json, minify_json, sort_keys};
+use crate::jwt::claim_label;
+use crate::permalink;
+use crate::profile::{check_years, Profile, Settings};
+use crate::rewrite::{shift_text, Shift};
+use crate::rules::{add_rule, PathPattern, Rule, RuleAction};
+use crate::scanner::Scanner;
+use chrono::{Duration, NaiveDateTime};
+use egui::{Response, ScrollArea, Ui};
 
 fn add_copiable_label(text: String, ui: &mut Ui, with_hover_text: bool) -> Response {
     let mut label = ui.add(egui::Label::new(&text).sense(egui::Sense::click()));
@@ -22,35 +36,245 @@ fn add_copiable_label(text: String, ui: &mut Ui, with_hover_text: bool) -> Respo
     label
 }
 
+/// What was done to a row of the timestamps table.
+enum TableAction {
+    /// Its time was edited to this text.
+    Edit(Hit, String),
+    /// It was clicked, to be shown in the editor.
+    Select(Hit),
+}
+
+enum MenuChoice {
+    Rule(Rule),
+    Exclude(PathPattern),
+}
+
+#[derive(Clone, Copy, serde::Deserialize, serde::Serialize)]
 enum SortBy {
     Time,
     Path,
 }
-pub struct TemplateApp {
-    // Example stuff:
+
+fn sort_hits(hits: &mut [Hit], sort_by: SortBy, ascend: bool) {
+    match sort_by {
+        SortBy::Time => hits.sort_by_key(|hit| (hit.ts, hit.path.0.clone())),
+        SortBy::Path => hits.sort_by_key(|hit| hit.path.0.clone()),
+    }
+    if !ascend {
+        hits.reverse();
+    }
+}
+
+#[derive(PartialEq, serde::Deserialize, serde::Serialize)]
+enum View {
+    Timestamps,
+    Durations,
+}
+
+/// Which timestamps table rows stand out.
+struct Highlights<'a> {
+    /// Whether the hit appeared in the last reload.
+    is_new: &'a dyn Fn(&Hit) -> bool,
+    /// Longer intervals between consecutive rows are gaps.
+    gap_threshold: Option<Duration>,
+    /// In live mode, the current time, which past and future timestamps are colored against.
+    now: Option<Timestamp>,
+}
+
+/// How timestamps after the current time are colored in live mode.
+const FUTURE_COLOR: egui::Color32 = egui::Color32::from_rgb(90, 170, 90);
+
+/// What a permalink restores: the document and how it's read.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct LinkState {
+    json_body: String,
     min_year: i32,
     max_year: i32,
-    json_body: String,
     fmt: String,
-    anchor: i64,
-    highlighted_path: Option<JsonPath>,
+    time_zone: Tz,
+    anchors: Vec<Anchor>,
+    sort_by: SortBy,
+    ascend: bool,
+}
+
+/// We derive Deserialize/Serialize so we can persist app state on shutdown.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct TemplateApp {
+    settings: Settings,
+    /// Not saved: they can be clipboard captures, or hold JWTs and other secrets.
+    #[serde(skip)]
+    documents: Documents,
+    /// Each gets a Relative column.
+    anchors: Vec<Anchor>,
     sort_by: SortBy,
     ascend: bool,
+    /// Longer intervals between consecutive rows are highlighted. Zero disables it.
+    gap_threshold_secs: f64,
+    /// Keep up with the current time: repaint every second, and color and filter by it.
+    live: bool,
+    /// Applied in live mode.
+    expiry_filter: ExpiryFilter,
+    /// The time zone the converter reads dates without one in.
+    converter_time_zone: Tz,
+    #[serde(skip)]
+    converter_input: String,
+    #[serde(skip)]
+    shift_form: ShiftForm,
+    /// Why the last edit or selection of a row in the table failed.
+    #[serde(skip)]
+    table_error: Option<String>,
+    /// The bytes to select in the editor when it's next shown, once a row was clicked.
+    #[serde(skip)]
+    pending_selection: Option<std::ops::Range<usize>>,
+    /// Why the last Format, Minify or Sort keys failed.
+    #[serde(skip)]
+    format_error: Option<String>,
+    /// The open document's hits shown last frame, which the editor highlights.
+    #[serde(skip)]
+    editor_hits: Vec<Hit>,
+    #[serde(skip)]
+    scan_cache: Option<ScanCache>,
+    #[serde(skip)]
     instruction_open: bool,
+    view: View,
+    profiles: Vec<Profile>,
+    /// The name of the last profile applied, if the settings weren't changed since.
+    active_profile: Option<String>,
+    #[serde(skip)]
+    profile_editor: ProfileEditor,
+    #[serde(skip)]
+    new_include: String,
+    #[serde(skip)]
+    new_exclude: String,
+    #[serde(skip)]
+    new_format: String,
+    /// The outcome of the last "Copy link", or of opening one.
+    #[serde(skip)]
+    link_status: Option<Result<String, String>>,
+    #[serde(skip)]
+    file_dialogs: FileDialogs,
+    /// The outcome of the last file operation.
+    #[serde(skip)]
+    file_status: Option<Result<String, String>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    clipboard: ClipboardMonitor,
+}
+
+/// Where "Shift timestamps" moves them.
+#[derive(Clone, Copy, Default, PartialEq)]
+enum ShiftMode {
+    /// By a duration.
+    #[default]
+    By,
+    /// So the earliest is at a date.
+    Earliest,
+    /// So the anchor with this index is at a date.
+    Anchor(usize),
 }
-impl Default for TemplateApp {
-    fn default() -> Self {
-        let current_datetime = Utc::now();
 
-        // Extract the year from the current date and time
-        let current_year = current_datetime.year();
+/// Scratch state for "Shift timestamps".
+#[derive(Default)]
+struct ShiftForm {
+    mode: ShiftMode,
+    /// A duration, or a date when rebasing.
+    input: String,
+    status: Option<Result<String, String>>,
+}
+
+/// Scratch state for saving, importing and exporting profiles.
+#[derive(Default)]
+struct ProfileEditor {
+    name: String,
+    json: String,
+    error: Option<String>,
+}
 
+/// A tab's hits (with their spans), durations, and its JWTs' problems or its documents' errors.
+type Scan = (Vec<Hit>, Vec<(JsonPath, Duration)>, Vec<(bool, String)>);
+
+/// The shown tab's scan, kept until its documents or the scan settings change, so they aren't
+/// parsed and scanned again every frame.
+struct ScanCache {
+    /// Whether it's the combined tab's.
+    combined: bool,
+    /// The name and text of each document scanned.
+    documents: Vec<(String, String)>,
+    scanner: Scanner,
+    scan: Result<Scan, String>,
+}
+
+impl ScanCache {
+    /// The shown tab's scan, from `cache` if it's still up to date.
+    fn get(
+        cache: &mut Option<ScanCache>,
+        documents: &Documents,
+        scanner: Scanner,
+    ) -> Result<Scan, String> {
+        let active = documents.active();
+        let scanned = match active {
+            Some(document) => std::slice::from_ref(document),
+            None => documents.documents(),
+        };
+        let stale = cache.as_ref().map_or(true, |cache| {
+            let unchanged = cache
+                .documents
+                .iter()
+                .map(|(name, text)| (name, text))
+                .eq(scanned
+                    .iter()
+                    .map(|document| (&document.name, &document.json_body)));
+            cache.combined != active.is_none() || cache.scanner != scanner || !unchanged
+        });
+        if stale {
+            *cache = None;
+        }
+        let cache = cache.get_or_insert_with(|| {
+            let scan = match active {
+                Some(document) => document.parse().map(|input| {
+                    let problems = input.problems().into_iter().map(|problem| (false, problem));
+                    (
+                        document.scan(&input, &scanner),
+                        scanner.durations(&input.value),
+                        problems.collect(),
+                    )
+                }),
+                None => {
+                    let (hits, errors) = documents.combined_hits(&scanner);
+                    let errors = errors.into_iter().map(|error| (true, error));
+                    Ok((
+                        hits,
+                        documents.combined_durations(&scanner),
+                        errors.collect(),
+                    ))
+                }
+            };
+            ScanCache {
+                combined: active.is_none(),
+                documents: scanned
+                    .iter()
+                    .map(|document| (document.name.clone(), document.json_body.clone()))
+                    .collect(),
+                scanner,
+                scan,
+            }
+        });
+        cache.scan.clone()
+    }
+}
+
+impl Default for TemplateApp {
+    fn default() -> Self {
         Self {
-            // Example stuff:
-            min_year: current_year - 2,
-            max_year: current_year + 3,
-            anchor: 1692694500,
-            json_body: r#"{
+            settings: Settings::default(),
+            anchors: vec![Anchor::new(
+                "anchor",
+                AnchorSource::Fixed(Timestamp::from_secs(1692694500)),
+            )],
+            documents: Documents::new(Document::new(
+                "Example".to_owned(),
+                None,
+                r#"{
   "field1": 1692694500,
   "field2": "I am a string",
   "field3": [1692684500, 1692693500, 1692699500],
@@ -59,29 +283,411 @@ impl Default for TemplateApp {
     "subfield2": 1692694500
   }
 }"#
-            .to_owned(),
-            fmt: "%Y-%m-%d %H:%M:%S".to_owned(),
-            highlighted_path: None,
+                .to_owned(),
+            )),
             sort_by: SortBy::Time,
             ascend: true,
+            gap_threshold_secs: 0.0,
+            live: false,
+            expiry_filter: ExpiryFilter::All,
+            converter_time_zone: Tz::Utc,
+            converter_input: String::new(),
+            shift_form: ShiftForm::default(),
+            table_error: None,
+            pending_selection: None,
+            format_error: None,
+            editor_hits: vec![],
+            scan_cache: None,
             instruction_open: false,
+            view: View::Timestamps,
+            profiles: vec![],
+            active_profile: None,
+            profile_editor: ProfileEditor::default(),
+            new_include: String::new(),
+            new_exclude: String::new(),
+            new_format: String::new(),
+            link_status: None,
+            file_dialogs: FileDialogs::default(),
+            file_status: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            clipboard: ClipboardMonitor::default(),
         }
     }
 }
 
 impl TemplateApp {
     /// Called once before the first frame.
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         // This is also where you can customize the look and feel of egui using
         // `cc.egui_ctx.set_visuals` and `cc.egui_ctx.set_fonts`.
 
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
-        // if let Some(storage) = cc.storage {
-        //     return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
-        // }
-        Self::default()
-        // Default::default()
+        let mut app: Self = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default();
+        match permalink::decode::<LinkState>(&permalink::location_fragment()) {
+            Some(Ok(state)) => match check_years(state.min_year, state.max_year) {
+                Ok(()) => app.apply_link_state(state),
+                Err(err) => app.link_status = Some(Err(format!("bad link: {}", err))),
+            },
+            Some(Err(err)) => app.link_status = Some(Err(err)),
+            None => {}
+        }
+        app
+    }
+
+    /// Opens the file in a new tab.
+    pub fn open_file(&mut self, file: OpenedFile) {
+        self.file_status = Some(Ok(format!("Opened {}", file.name)));
+        self.documents.open(file);
+    }
+
+    /// The shown document, parsed.
+    fn active_value(&self) -> Result<serde_json::Value, String> {
+        let document = self
+            .documents
+            .active()
+            .ok_or("Select a single document first")?;
+        document
+            .parse()
+            .map(|input| input.value)
+            .map_err(|err| format!("{}: {}", document.name, err))
+    }
+
+    /// The hits of the shown tab, sorted like the table.
+    fn table_hits(&self) -> Result<Vec<Hit>, String> {
+        let scanner = Scanner::from_settings(&self.settings);
+        let mut hits = match self.documents.active() {
+            Some(_) => scanner.scan(&self.active_value()?),
+            None => self.documents.combined_hits(&scanner).0,
+        };
+        if self.live {
+            let now = Timestamp::now();
+            hits.retain(|hit| self.expiry_filter.keeps(hit.ts, now));
+        }
+        sort_hits(&mut hits, self.sort_by, self.ascend);
+        Ok(hits)
+    }
+
+    /// A name for a file derived from the shown one, e.g. "events.annotated.json".
+    fn derived_file_name(&self, suffix: &str) -> String {
+        let stem = self
+            .documents
+            .active()
+            .map(|document| document.name.as_str())
+            .map(|name| name.strip_suffix(".json").unwrap_or(name))
+            .unwrap_or("combined");
+        format!("{}.{}", stem, suffix)
+    }
+
+    fn save_derived_file(&mut self, suffix: &str, contents: Result<String, String>) {
+        let file_name = self.derived_file_name(suffix);
+        self.file_status =
+            match contents.and_then(|contents| self.file_dialogs.save(&file_name, &contents)) {
+                Ok(Some(saved)) => Some(Ok(format!("Saved {}", saved))),
+                Ok(None) => None,
+                Err(err) => Some(Err(err)),
+            };
+    }
+
+    fn file_menu_ui(&mut self, ui: &mut Ui) {
+        if ui.button("Open…").clicked() {
+            ui.close_menu();
+            self.file_dialogs.open();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let active = self.documents.active();
+            let mut watched = active.map_or(false, |document| document.watch.is_some());
+            let has_file = active.map_or(false, |document| document.path.is_some());
+            let checkbox = ui
+                .add_enabled(
+                    has_file,
+                    egui::Checkbox::new(&mut watched, "Watch for changes"),
+                )
+                .on_hover_text("Reload the file whenever it changes on disk")
+                .on_disabled_hover_text("Only files opened from disk can be watched");
+            if checkbox.changed() {
+                let scanner = Scanner::from_settings(&self.settings);
+                self.documents.set_watched(watched, &scanner);
+            }
+        }
+        if ui
+            .button("Save annotated…")
+            .on_hover_text(
+                "The document, with every timestamp replaced by {value, time, interpretation}",
+            )
+            .clicked()
+        {
+            ui.close_menu();
+            let annotated = self.active_value().map(|value| {
+                let settings = &self.settings;
+                let hits = Scanner::from_settings(settings).scan(&value);
+                let annotated = annotate(&value, &hits, &settings.fmt, settings.time_zone);
+                serde_json::to_string_pretty(&annotated).expect("JSON values are serializable")
+            });
+            self.save_derived_file("annotated.json", annotated);
+        }
+        if ui
+            .button("Export table…")
+            .on_hover_text("The timestamps table, as CSV")
+            .clicked()
+        {
+            ui.close_menu();
+            let csv = self.table_hits().map(|hits| {
+                let settings = &self.settings;
+                let anchors = resolve_anchors(&self.anchors, &hits, Timestamp::now());
+                hits_to_csv(
+                    &hits,
+                    &settings.fmt,
+                    settings.time_zone,
+                    &anchors,
+                    settings.duration_style,
+                )
+            });
+            self.save_derived_file("csv", csv);
+        }
+    }
+
+    /// Shows a payload captured from the clipboard, in the "Clipboard" tab.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn show_clipboard_payload(&mut self, payload: String) {
+        self.documents.replace_or_open("Clipboard", payload);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn clipboard_menu_ui(&mut self, ui: &mut Ui) {
+        let clipboard = &mut self.clipboard;
+        ui.checkbox(&mut clipboard.enabled, "Monitor clipboard")
+            .on_hover_text("Load JSON into the \"Clipboard\" tab whenever it's copied");
+        ui.horizontal(|ui| {
+            ui.label("History size:");
+            ui.add(egui::DragValue::new(&mut clipboard.history_len).clamp_range(1..=100));
+        });
+        ui.separator();
+        if clipboard.history().is_empty() {
+            ui.weak("Nothing captured yet");
+        }
+        let mut chosen = None;
+        for payload in clipboard.history() {
+            let preview: String = payload.split_whitespace().collect::<Vec<_>>().join(" ");
+            let preview = match preview.char_indices().nth(40) {
+                Some((end, _)) => format!("{}…", &preview[..end]),
+                None => preview,
+            };
+            if ui.button(preview).clicked() {
+                chosen = Some(payload.clone());
+            }
+        }
+        if let Some(payload) = chosen {
+            ui.close_menu();
+            self.show_clipboard_payload(payload);
+        }
+    }
+
+    fn link_state(&self) -> LinkState {
+        LinkState {
+            json_body: self
+                .documents
+                .active()
+                .or(self.documents.documents().first())
+                .map_or(String::new(), |document| document.json_body.clone()),
+            min_year: self.settings.min_year,
+            max_year: self.settings.max_year,
+            fmt: self.settings.fmt.clone(),
+            time_zone: self.settings.time_zone,
+            anchors: self.anchors.clone(),
+            sort_by: self.sort_by,
+            ascend: self.ascend,
+        }
+    }
+
+    fn apply_link_state(&mut self, state: LinkState) {
+        self.documents
+            .replace_or_open("Shared link", state.json_body);
+        self.settings.min_year = state.min_year;
+        self.settings.max_year = state.max_year;
+        self.settings.fmt = state.fmt;
+        self.settings.time_zone = state.time_zone;
+        self.anchors = state.anchors;
+        self.sort_by = state.sort_by;
+        self.ascend = state.ascend;
+    }
+
+    fn share_ui(&mut self, ui: &mut Ui) {
+        if ui
+            .button("Copy link")
+            .on_hover_text("A link that opens this document with these settings")
+            .clicked()
+        {
+            let link = permalink::encode(&permalink::page_url(), &self.link_state());
+            permalink::set_location(&link);
+            self.link_status = Some(if link.len() > permalink::MAX_LINK_LEN {
+                Err(format!(
+                    "Copied, but the link is {} KB long. Some chat apps and browsers truncate \
+                    links over {} KB, consider sharing the file instead.",
+                    link.len() / 1000,
+                    permalink::MAX_LINK_LEN / 1000
+                ))
+            } else {
+                Ok(format!("Copied ({} characters)", link.len()))
+            });
+            ui.output_mut(|po| po.copied_text = link);
+        }
+        match &self.link_status {
+            Some(Ok(status)) => {
+                ui.label(status);
+            }
+            Some(Err(err)) => {
+                ui.colored_label(ui.visuals().warn_fg_color, err);
+            }
+            None => {}
+        }
+    }
+
+    /// Switches to the profile's settings, and remembers the profile for the dropdown.
+    pub fn apply_profile(&mut self, profile: Profile) {
+        self.settings = profile.settings.clone();
+        self.active_profile = Some(profile.name.clone());
+        self.profiles
+            .retain(|existing| existing.name != profile.name);
+        self.profiles.push(profile);
+    }
+
+    fn profiles_ui(&mut self, ui: &mut Ui) {
+        let selected = self.active_profile.clone().unwrap_or("(custom)".to_owned());
+        let mut to_apply = None;
+        egui::ComboBox::from_label("Profile")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                for profile in &self.profiles {
+                    let is_active = self.active_profile.as_ref() == Some(&profile.name);
+                    if ui.selectable_label(is_active, &profile.name).clicked() {
+                        to_apply = Some(profile.clone());
+                    }
+                }
+            });
+        if let Some(profile) = to_apply {
+            self.apply_profile(profile);
+        }
+        let editor = &mut self.profile_editor;
+        let mut save_clicked = false;
+        let mut export_clicked = false;
+        let mut import_clicked = false;
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut editor.name).hint_text("Profile name"));
+            save_clicked = ui.button("Save").clicked() && !editor.name.is_empty();
+        });
+        ui.horizontal(|ui| {
+            export_clicked = ui
+                .button("Export")
+                .on_hover_text("Copy the current settings as a profile file")
+                .clicked();
+            import_clicked = ui
+                .button("Import")
+                .on_hover_text("Load the profile pasted below")
+                .clicked();
+        });
+        ui.add(
+            egui::TextEdit::multiline(&mut editor.json)
+                .hint_text("Paste a profile here")
+                .desired_rows(2)
+                .desired_width(f32::INFINITY),
+        );
+        if let Some(err) = &editor.error {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+
+        if save_clicked {
+            let profile = Profile::new(editor.name.clone(), self.settings.clone());
+            self.apply_profile(profile);
+        } else if export_clicked {
+            let name = self.active_profile.clone().unwrap_or(editor.name.clone());
+            let json = Profile::new(name, self.settings.clone()).to_json();
+            ui.output_mut(|po| po.copied_text = json.clone());
+            editor.json = json;
+        } else if import_clicked {
+            match Profile::from_json(&editor.json) {
+                Ok(profile) => {
+                    editor.error = None;
+                    self.apply_profile(profile);
+                }
+                Err(err) => editor.error = Some(err),
+            }
+        }
+    }
+
+    fn time_zone_ui(id_source: &str, time_zone: &mut Tz, ui: &mut Ui) {
+        egui::ComboBox::from_id_source(id_source)
+            .selected_text(match time_zone {
+                Tz::Utc => "UTC",
+                Tz::Local => "Local",
+                Tz::Fixed(_) => "Fixed offset",
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(time_zone, Tz::Utc, "UTC");
+                ui.selectable_value(time_zone, Tz::Local, "Local");
+                if ui
+                    .selectable_label(matches!(time_zone, Tz::Fixed(_)), "Fixed offset")
+                    .clicked()
+                    && !matches!(time_zone, Tz::Fixed(_))
+                {
+                    *time_zone = Tz::Fixed(0);
+                }
+            });
+        if let Tz::Fixed(offset) = time_zone {
+            let mut minutes = *offset / 60;
+            ui.add(
+                egui::DragValue::new(&mut minutes)
+                    .clamp_range(-(23 * 60 + 59)..=(23 * 60 + 59))
+                    .speed(15.0)
+                    .custom_formatter(|minutes, _| Tz::Fixed(minutes as i32 * 60).to_string())
+                    .custom_parser(|s| match s.parse() {
+                        Ok(Tz::Fixed(offset)) => Some(f64::from(offset / 60)),
+                        _ => None,
+                    }),
+            );
+            *offset = minutes * 60;
+        }
+    }
+
+    /// A list with remove buttons, and a text box to add more.
+    fn list_ui<T: std::fmt::Display>(
+        items: &mut Vec<T>,
+        new_item: &mut String,
+        hint: &str,
+        parse: impl Fn(&str) -> Result<T, String>,
+        ui: &mut Ui,
+    ) {
+        let mut to_remove = None;
+        for (i, item) in items.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                    to_remove = Some(i);
+                }
+                ui.label(item.to_string());
+            });
+        }
+        if let Some(i) = to_remove {
+            items.remove(i);
+        }
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(new_item).hint_text(hint));
+            match parse(new_item) {
+                Ok(item) => {
+                    if ui.button("Add").clicked() {
+                        items.push(item);
+                        new_item.clear();
+                    }
+                }
+                Err(err) => {
+                    ui.add_enabled(false, egui::Button::new("Add"))
+                        .on_disabled_hover_text(err);
+                }
+            }
+        });
     }
 
     fn clickable_strong_label(text: String, ui: &mut Ui) -> bool {
@@ -91,16 +697,23 @@ impl TemplateApp {
         );
         label.clicked()
     }
+    /// Returns what was done to a row, if anything.
     fn table_ui(
-        x: &[(JsonPath, i64)],
-        fmt: &str,
-        anchor: &mut i64,
+        x: &[Hit],
+        highlights: &Highlights<'_>,
+        settings: &mut Settings,
+        anchors: &mut Vec<Anchor>,
         sort_by: &mut SortBy,
         ascend: &mut bool,
         ui: &mut egui::Ui,
-    ) {
+    ) -> Option<TableAction> {
         use egui_extras::{Column, TableBuilder};
 
+        let resolved = resolve_anchors(anchors, x, Timestamp::now());
+        // The path and text of the time being edited, if any. Kept in egui's memory, since
+        // it only matters while the table is shown.
+        let edit_id = egui::Id::new("time edit");
+        let mut editing: Option<(JsonPath, String)> = ui.data_mut(|data| data.get_temp(edit_id));
         let table = TableBuilder::new(ui)
             .striped(true)
             .resizable(true)
@@ -109,6 +722,9 @@ impl TemplateApp {
             .column(Column::auto())
             .column(Column::auto())
             .column(Column::auto())
+            .column(Column::auto())
+            .columns(Column::auto(), resolved.len())
+            .column(Column::auto())
             .column(Column::remainder())
             .min_scrolled_height(0.0);
         let arrow = if *ascend { " ↗" } else { " ↘" };
@@ -118,6 +734,9 @@ impl TemplateApp {
         };
         let mut time_clicked = false;
         let mut path_clicked = false;
+        let mut new_rule = None;
+        let mut anchor_choice = None;
+        let mut action = None;
         table
             .header(20.0, |mut header| {
                 header.col(|ui| {
@@ -125,18 +744,31 @@ impl TemplateApp {
                 });
                 header.col(|ui| {
                     time_clicked |=
-                        Self::clickable_strong_label(format!("Unix-time{}", time_arrow), ui);
+                        Self::clickable_strong_label(format!("Value{}", time_arrow), ui);
                     // ui.strong(format!("Unix-time{}", time_arrow));
                 });
+                header.col(|ui| {
+                    ui.strong("Interpretation");
+                });
+                header.col(|ui| {
+                    ui.strong("Confidence");
+                });
                 header.col(|ui| {
                     time_clicked |=
                         Self::clickable_strong_label(format!("Human Readable{}", time_arrow), ui);
                     // ui.strong(format!("Human readable{}", time_arrow));
                 });
+                for (name, _) in &resolved {
+                    header.col(|ui| {
+                        time_clicked |= Self::clickable_strong_label(
+                            format!("Relative to {}{}", name, time_arrow),
+                            ui,
+                        );
+                    });
+                }
                 header.col(|ui| {
-                    time_clicked |=
-                        Self::clickable_strong_label(format!("Relative{}", time_arrow), ui);
-                    // ui.strong(format!("Relative{}", time_arrow));
+                    ui.strong("Δ prev")
+                        .on_hover_text("The time since the previous row, in the current order");
                 });
                 header.col(|ui| {
                     path_clicked |=
@@ -144,43 +776,767 @@ impl TemplateApp {
                 });
             })
             .body(|mut body| {
-                for (row_index, (path, ts)) in x.iter().enumerate() {
+                let deltas = deltas(x);
+                for ((row_index, hit), delta) in x.iter().enumerate().zip(deltas) {
+                    let Hit { path, raw, ts, .. } = hit;
                     let row_height = 18.0;
                     body.row(row_height, |mut row| {
                         row.col(|ui| {
-                            ui.label(row_index.to_string());
+                            let (text, hover_text) = match (highlights.is_new)(hit) {
+                                true => (
+                                    egui::RichText::new(format!("{} new", row_index))
+                                        .color(ui.visuals().warn_fg_color),
+                                    "Appeared in the last reload.\nClick to show in the editor.",
+                                ),
+                                false => (
+                                    egui::RichText::new(row_index.to_string()),
+                                    "Click to show in the editor.",
+                                ),
+                            };
+                            let label = ui
+                                .add(egui::Label::new(text).sense(egui::Sense::click()))
+                                .on_hover_text(hover_text);
+                            if label.clicked() {
+                                action = Some(TableAction::Select(hit.clone()));
+                            }
                         });
                         row.col(|ui| {
-                            let response = add_copiable_label(ts.to_string(), ui, false)
+                            add_copiable_label(raw.clone(), ui, false)
                                 .on_hover_text(
-                                    "Left click to copy.\nRight click to set as anchor.".to_owned(),
-                                );
-                            if response.clicked_by(egui::PointerButton::Secondary) {
-                                *anchor = *ts
-                            }
+                                    "Left click to copy.\nRight click to set an anchor here."
+                                        .to_owned(),
+                                )
+                                .context_menu(|ui| {
+                                    anchor_choice = anchor_choice.take().or(Self::anchor_menu_ui(
+                                        anchors,
+                                        *ts,
+                                        Some(path),
+                                        ui,
+                                    ));
+                                });
                             // ui.label(ts.to_string());
                         });
                         row.col(|ui| {
-                            add_copiable_label(
-                                ts_to_str(*ts, fmt).unwrap_or("N/A".to_owned()),
-                                ui,
-                                true,
-                            );
+                            let text = if hit.pinned {
+                                format!("{} (pinned)", hit.interpretation)
+                            } else {
+                                hit.interpretation.to_string()
+                            };
+                            ui.add(egui::Label::new(text).sense(egui::Sense::click()))
+                                .on_hover_text("Right click to override.")
+                                .context_menu(|ui| {
+                                    new_rule = new_rule.take().or(Self::rule_menu_ui(hit, ui));
+                                });
                         });
                         row.col(|ui| {
-                            let diff = ts - *anchor;
-                            let abs_diff = diff.abs();
-                            let hours = abs_diff / 3600;
-                            let minutes = (abs_diff % 3600) / 60;
-                            let seconds = abs_diff % 60;
-                            let sign = if diff < 0 { "-" } else { "+" };
-                            add_copiable_label(
-                                format!("{}{:02}:{:02}:{:02}", sign, hours, minutes, seconds),
-                                ui,
-                                true,
-                            );
-                            // let dur = chrono::Duration::seconds(ts - anchor);
-                            // ui.label(dur.to_string());
+                            ui.label(format!("{:.0}%", hit.confidence * 100.0));
+                        });
+                        row.col(|ui| {
+                            if let Some(now) = highlights.now {
+                                ui.visuals_mut().override_text_color = Some(if *ts <= now {
+                                    ui.visuals().error_fg_color
+                                } else {
+                                    FUTURE_COLOR
+                                });
+                            }
+                            let formatted =
+                                timestamp_to_str(*ts, &settings.fmt, settings.time_zone)
+                                    .unwrap_or("N/A".to_owned());
+                            match &mut editing {
+                                Some((editing_path, text)) if editing_path == path => {
+                                    let response = ui.add(
+                                        egui::TextEdit::singleline(text)
+                                            .id(edit_id)
+                                            .desired_width(ui.available_width().max(160.0)),
+                                    );
+                                    if response.lost_focus() {
+                                        if ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                                            action =
+                                                Some(TableAction::Edit(hit.clone(), text.clone()));
+                                        }
+                                        editing = None;
+                                    }
+                                }
+                                _ => {
+                                    let label = add_copiable_label(formatted.clone(), ui, false)
+                                        .on_hover_text("Click to copy, double click to edit.");
+                                    if label.double_clicked() {
+                                        editing = Some((path.clone(), formatted));
+                                        ui.memory_mut(|memory| memory.request_focus(edit_id));
+                                    }
+                                }
+                            }
+                        });
+                        for (_, anchor) in &resolved {
+                            row.col(|ui| match anchor {
+                                Some(anchor) => match ts.since(*anchor) {
+                                    Some(diff) => {
+                                        add_copiable_label(
+                                            settings.duration_style.format_relative(diff),
+                                            ui,
+                                            true,
+                                        );
+                                    }
+                                    None => {
+                                        ui.weak("N/A").on_hover_text("Too far from the anchor");
+                                    }
+                                },
+                                None => {
+                                    ui.weak("N/A")
+                                        .on_hover_text("No timestamp matches the anchor's path");
+                                }
+                            });
+                        }
+                        row.col(|ui| {
+                            let Some(delta) = delta else {
+                                return;
+                            };
+                            let is_gap = highlights
+                                .gap_threshold
+                                .map_or(false, |threshold| delta.abs() > threshold);
+                            if is_gap {
+                                ui.visuals_mut().override_text_color =
+                                    Some(ui.visuals().warn_fg_color);
+                            }
+                            add_copiable_label(settings.duration_style.format(delta), ui, true);
+                        });
+                        row.col(|ui| {
+                            ui.style_mut().wrap = Some(false);
+                            add_copiable_label(format!("{}", path), ui, true).context_menu(|ui| {
+                                new_rule = new_rule.take().or(Self::rule_menu_ui(hit, ui));
+                            });
+                            if let Some(label) = claim_label(hit) {
+                                ui.weak(format!("({})", label));
+                            }
+                        });
+                    });
+                }
+            });
+        ui.data_mut(|data| match editing {
+            Some(editing) => data.insert_temp(edit_id, editing),
+            None => data.remove::<(JsonPath, String)>(edit_id),
+        });
+        Self::apply_anchor_choice(anchors, anchor_choice);
+        match new_rule {
+            Some(MenuChoice::Rule(rule)) => add_rule(&mut settings.rules, rule),
+            Some(MenuChoice::Exclude(pattern)) => settings.exclude.push(pattern),
+            None => {}
+        }
+        if time_clicked {
+            *sort_by = SortBy::Time
+        } else if path_clicked {
+            *sort_by = SortBy::Path
+        };
+        if time_clicked | path_clicked {
+            *ascend = !*ascend;
+        };
+        action
+    }
+
+    /// The right click menu for anchoring at a time, or at the path it was found at. Returns the
+    /// anchor to change (or `None` for a new one), and its new source.
+    fn anchor_menu_ui(
+        anchors: &[Anchor],
+        ts: Timestamp,
+        path: Option<&JsonPath>,
+        ui: &mut Ui,
+    ) -> Option<(Option<usize>, AnchorSource)> {
+        let mut choice = None;
+        let at_time = AnchorSource::Fixed(ts);
+        let at_path = path.map(|path| (path, AnchorSource::Path(PathPattern::exact(path))));
+        for (i, anchor) in anchors.iter().enumerate() {
+            ui.menu_button(format!("Move \"{}\"", anchor.name), |ui| {
+                if ui.button("To this time").clicked() {
+                    choice = Some((Some(i), at_time.clone()));
+                    ui.close_menu();
+                }
+                if let Some((path, at_path)) = &at_path {
+                    if ui.button(format!("To follow {}", path)).clicked() {
+                        choice = Some((Some(i), at_path.clone()));
+                        ui.close_menu();
+                    }
+                }
+            });
+        }
+        if ui.button("New anchor at this time").clicked() {
+            choice = Some((None, at_time));
+            ui.close_menu();
+        }
+        if let Some((path, at_path)) = at_path {
+            if ui
+                .button(format!("New anchor following {}", path))
+                .clicked()
+            {
+                choice = Some((None, at_path));
+                ui.close_menu();
+            }
+        }
+        choice
+    }
+
+    fn apply_anchor_choice(
+        anchors: &mut Vec<Anchor>,
+        choice: Option<(Option<usize>, AnchorSource)>,
+    ) {
+        match choice {
+            Some((Some(i), source)) => anchors[i].source = source,
+            Some((None, source)) => {
+                let name = match &source {
+                    AnchorSource::Path(pattern) => pattern.to_string(),
+                    _ => format!("anchor {}", anchors.len() + 1),
+                };
+                anchors.push(Anchor { name, source });
+            }
+            None => {}
+        }
+    }
+
+    /// The open document's text, with its timestamps highlighted and explained on hover.
+    fn editor_ui(
+        document: &mut Document,
+        hits: &[Hit],
+        pending_selection: &mut Option<std::ops::Range<usize>>,
+        settings: &Settings,
+        anchors: &[Anchor],
+        ui: &mut Ui,
+    ) {
+        let editor_id = egui::Id::new("json editor");
+        let spans: Vec<_> = hits
+            .iter()
+            .filter_map(|hit| Some(hit.span.as_ref()?.range.clone()))
+            .collect();
+        let theme = editor::Theme::from_style(ui.style());
+        let mut layouter = |ui: &Ui, text: &str, wrap_width: f32| {
+            let mut job = editor::cached_layout_job(ui.ctx(), text, &spans, &theme);
+            job.wrap.max_width = wrap_width;
+            ui.fonts(|fonts| fonts.layout_job(job))
+        };
+        let font = egui::TextStyle::Monospace.resolve(ui.style());
+        let lines = document.json_body.split('\n').count();
+        let gutter_width = ui
+            .fonts(|fonts| fonts.glyph_width(&font, '0') * lines.to_string().len() as f32)
+            + ui.spacing().item_spacing.x;
+        let (gutter, output) = ui
+            .horizontal_top(|ui| {
+                let (gutter, _) =
+                    ui.allocate_exact_size(egui::vec2(gutter_width, 0.0), egui::Sense::hover());
+                let output = egui::TextEdit::multiline(&mut document.json_body)
+                    .id(editor_id)
+                    .hint_text("Paste your JSON here!")
+                    .desired_width(f32::INFINITY)
+                    .layouter(&mut layouter)
+                    .show(ui);
+                (gutter, output)
+            })
+            .inner;
+        Self::line_numbers_ui(gutter, &output, font, ui);
+        if let Some(range) = pending_selection.take() {
+            Self::select_in_editor(&document.json_body, range, editor_id, &output, ui);
+        }
+        let Some(pointer) = output.response.hover_pos() else {
+            return;
+        };
+        let cursor = output
+            .galley
+            .cursor_from_pos(pointer - output.text_draw_pos);
+        let offset = editor::byte_offset(&document.json_body, cursor.ccursor.index);
+        let hovered = hits.iter().find(|hit| {
+            hit.span
+                .as_ref()
+                .map_or(false, |span| span.range.contains(&offset))
+        });
+        let Some(hit) = hovered else {
+            return;
+        };
+        let resolved = resolve_anchors(anchors, hits, Timestamp::now());
+        output.response.on_hover_ui_at_pointer(|ui| {
+            ui.strong(
+                timestamp_to_str(hit.ts, &settings.fmt, settings.time_zone)
+                    .unwrap_or("N/A".to_owned()),
+            );
+            for (name, anchor) in resolved {
+                if let Some(diff) = anchor.and_then(|anchor| hit.ts.since(anchor)) {
+                    let relative = settings.duration_style.format_relative(diff);
+                    ui.label(format!("{} relative to {}", relative, name));
+                }
+            }
+            ui.weak(format!("{} at {}", hit.interpretation, hit.path));
+        });
+    }
+
+    /// Buttons that lay the open document out again, or sort its keys.
+    fn format_buttons_ui(document: &mut Document, error: &mut Option<String>, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let actions = [
+                (
+                    "Format",
+                    "Pretty-print, keeping the key order",
+                    format_json as fn(&str) -> Result<String, String>,
+                ),
+                (
+                    "Minify",
+                    "Remove whitespace; streams become NDJSON",
+                    minify_json,
+                ),
+                ("Sort keys", "Sort every object's keys", sort_keys),
+            ];
+            for (label, hover, action) in actions {
+                if ui.button(label).on_hover_text(hover).clicked() {
+                    *error = action(&document.json_body)
+                        .map(|text| document.json_body = text)
+                        .err();
+                }
+            }
+        });
+        if let Some(err) = error {
+            ui.colored_label(ui.visuals().error_fg_color, err.as_str());
+        }
+    }
+
+    /// Numbers the editor's lines in the gutter to its left. Wrapped rows aren't numbered.
+    fn line_numbers_ui(
+        gutter: egui::Rect,
+        output: &egui::text_edit::TextEditOutput,
+        font: egui::FontId,
+        ui: &Ui,
+    ) {
+        let visible = ui.clip_rect().y_range();
+        let color = ui.visuals().weak_text_color();
+        let mut line = 1;
+        let mut starts_line = true;
+        for row in &output.galley.rows {
+            let top = output.text_draw_pos.y + row.rect.top();
+            if starts_line && visible.contains(&(top + row.rect.height() / 2.0)) {
+                ui.painter().text(
+                    egui::pos2(gutter.right(), top),
+                    egui::Align2::RIGHT_TOP,
+                    line,
+                    font.clone(),
+                    color,
+                );
+            }
+            if row.ends_with_newline {
+                line += 1;
+            }
+            starts_line = row.ends_with_newline;
+        }
+    }
+
+    /// Selects the bytes in the editor and scrolls to them.
+    fn select_in_editor(
+        text: &str,
+        range: std::ops::Range<usize>,
+        editor_id: egui::Id,
+        output: &egui::text_edit::TextEditOutput,
+        ui: &mut Ui,
+    ) {
+        use egui::text::{CCursor, CCursorRange};
+
+        let (Some(before), Some(selected)) = (text.get(..range.start), text.get(range)) else {
+            return;
+        };
+        let start = CCursor::new(before.chars().count());
+        let end = CCursor::new(start.index + selected.chars().count());
+        let mut state = output.state.clone();
+        state.set_ccursor_range(Some(CCursorRange::two(start, end)));
+        state.store(ui.ctx(), editor_id);
+        ui.memory_mut(|memory| memory.request_focus(editor_id));
+        let rect = output
+            .galley
+            .pos_from_cursor(&output.galley.from_ccursor(start))
+            .translate(output.text_draw_pos.to_vec2());
+        ui.scroll_to_rect(rect, Some(egui::Align::Center));
+    }
+
+    /// Moves every timestamp in the open document, rewriting each in its own unit and format.
+    fn shift_ui(
+        form: &mut ShiftForm,
+        documents: &mut Documents,
+        settings: &Settings,
+        anchors: &[Anchor],
+        ui: &mut Ui,
+    ) {
+        let Some(document) = documents.active_mut() else {
+            ui.label("Select a tab to shift its timestamps.");
+            return;
+        };
+        let mode_text = |mode| match mode {
+            ShiftMode::By => "Move them by".to_owned(),
+            ShiftMode::Earliest => "Move the earliest to".to_owned(),
+            ShiftMode::Anchor(i) => match anchors.get(i) {
+                Some(anchor) => format!("Move \"{}\" to", anchor.name),
+                None => "Move the anchor to".to_owned(),
+            },
+        };
+        egui::ComboBox::from_id_source("shift mode")
+            .selected_text(mode_text(form.mode))
+            .show_ui(ui, |ui| {
+                let modes = [ShiftMode::By, ShiftMode::Earliest]
+                    .into_iter()
+                    .chain((0..anchors.len()).map(ShiftMode::Anchor));
+                for mode in modes {
+                    ui.selectable_value(&mut form.mode, mode, mode_text(mode));
+                }
+            });
+        let hint = match form.mode {
+            ShiftMode::By => "e.g. -1h 30m, 2w or 3.5s",
+            _ => "e.g. 2024-01-01 00:00",
+        };
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut form.input).hint_text(hint));
+            if ui.button("Apply").clicked() {
+                let scanner = Scanner::from_settings(settings);
+                let hits = scanner.scan_str(&document.json_body).unwrap_or_default();
+                let date = || {
+                    parse_date(&form.input, &settings.fmt, settings.time_zone)
+                        .ok_or("Not a date I can read")
+                };
+                let shift = match form.mode {
+                    ShiftMode::By => parse_duration(&form.input)
+                        .map(Shift::By)
+                        .ok_or("Not a duration I can read"),
+                    ShiftMode::Earliest => date().map(|to| Shift::Rebase { from: None, to }),
+                    ShiftMode::Anchor(i) => anchors
+                        .get(i)
+                        .and_then(|anchor| anchor.resolve(&hits, Timestamp::now()))
+                        .ok_or("The anchor has no time")
+                        .and_then(|from| {
+                            date().map(|to| Shift::Rebase {
+                                from: Some(from),
+                                to,
+                            })
+                        }),
+                };
+                form.status = Some(shift.map_err(str::to_owned).and_then(|shift| {
+                    document.json_body = shift_text(&document.json_body, &scanner, shift)?;
+                    let offset = shift.offset(&hits).unwrap_or_else(Duration::zero);
+                    Ok(format!(
+                        "Moved {} timestamps by {}",
+                        hits.len(),
+                        settings.duration_style.format_relative(offset)
+                    ))
+                }));
+            }
+        });
+        match &form.status {
+            Some(Ok(status)) => {
+                ui.label(status);
+            }
+            Some(Err(err)) => {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            None => {}
+        }
+    }
+
+    /// Converts a date typed in the box to its value in every epoch family.
+    fn converter_ui(
+        input: &mut String,
+        time_zone: &mut Tz,
+        settings: &Settings,
+        anchors: &mut Vec<Anchor>,
+        ui: &mut Ui,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label("Date:");
+            ui.add(egui::TextEdit::singleline(input).hint_text("e.g. 2023-08-22 08:55"))
+                .on_hover_text(
+                    "In the datetime format, RFC 3339, RFC 2822 or a common layout like 22 Aug 2023",
+                );
+        });
+        ui.horizontal(|ui| {
+            ui.label("Read in:");
+            Self::time_zone_ui("converter time zone", time_zone, ui);
+        });
+        if input.trim().is_empty() {
+            return;
+        }
+        let Some(ts) = parse_date(input, &settings.fmt, *time_zone) else {
+            ui.colored_label(ui.visuals().error_fg_color, "Not a date I can read");
+            return;
+        };
+        let mut choice = None;
+        egui::Grid::new("converter").striped(true).show(ui, |ui| {
+            for family in EPOCH_FAMILIES {
+                let Some(value) = family.value_of(ts) else {
+                    continue;
+                };
+                ui.label(family.name);
+                add_copiable_label(value, ui, false)
+                    .on_hover_text("Left click to copy.\nRight click to set an anchor here.")
+                    .context_menu(|ui| {
+                        choice = choice
+                            .take()
+                            .or(Self::anchor_menu_ui(anchors, ts, None, ui));
+                    });
+                ui.end_row();
+            }
+        });
+        Self::apply_anchor_choice(anchors, choice);
+    }
+
+    fn anchors_ui(anchors: &mut Vec<Anchor>, settings: &Settings, ui: &mut Ui) {
+        let mut to_remove = None;
+        for (i, anchor) in anchors.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.small_button("✖").clicked() {
+                    to_remove = Some(i);
+                }
+                ui.add(egui::TextEdit::singleline(&mut anchor.name).desired_width(100.0));
+                let sources = [
+                    AnchorSource::Fixed(match anchor.source {
+                        AnchorSource::Fixed(ts) => ts,
+                        _ => Timestamp::now(),
+                    }),
+                    AnchorSource::Now,
+                    AnchorSource::EarliestHit,
+                    AnchorSource::LatestHit,
+                ];
+                let source_text = |source: &AnchorSource| match source {
+                    AnchorSource::Fixed(_) => "fixed".to_owned(),
+                    source => source.to_string(),
+                };
+                egui::ComboBox::from_id_source(("anchor source", i))
+                    .selected_text(source_text(&anchor.source))
+                    .show_ui(ui, |ui| {
+                        for source in sources {
+                            let text = source_text(&source);
+                            ui.selectable_value(&mut anchor.source, source, text);
+                        }
+                    })
+                    .response
+                    .on_hover_text(
+                        "Right click a value in the table to make an anchor follow its path",
+                    );
+                if let AnchorSource::Fixed(ts) = &mut anchor.source {
+                    // The seconds chrono can show, so the anchor is always a date.
+                    let range = NaiveDateTime::MIN.timestamp()..=NaiveDateTime::MAX.timestamp();
+                    ui.add(
+                        egui::DragValue::new(&mut ts.secs)
+                            .speed(1.0)
+                            .clamp_range(range),
+                    );
+                    ui.label(
+                        timestamp_to_str(*ts, &settings.fmt, settings.time_zone)
+                            .unwrap_or("N/A".to_owned()),
+                    );
+                }
+            });
+        }
+        if let Some(i) = to_remove {
+            anchors.remove(i);
+        }
+        if ui.button("Add anchor").clicked() {
+            anchors.push(Anchor::new(
+                &format!("anchor {}", anchors.len() + 1),
+                AnchorSource::Fixed(Timestamp::now()),
+            ));
+        }
+    }
+
+    /// The right click menu for overriding how a hit's path is interpreted. A protobuf
+    /// Timestamp object can only be marked as not a timestamp: it has no epoch family.
+    fn rule_menu_ui(hit: &Hit, ui: &mut Ui) -> Option<MenuChoice> {
+        let mut choice = None;
+        let mut patterns = vec![PathPattern::exact(&hit.path)];
+        let any_index = PathPattern::any_index(&hit.path);
+        if any_index != patterns[0] {
+            patterns.push(any_index);
+        }
+        for pattern in patterns {
+            ui.menu_button(format!("Override {}", pattern), |ui| {
+                let families = match hit.interpretation {
+                    Interpretation::ProtoTimestamp => &[][..],
+                    _ => EPOCH_FAMILIES,
+                };
+                let actions = families
+                    .iter()
+                    .map(|family| RuleAction::Family(*family))
+                    .chain([RuleAction::NotATimestamp]);
+                for action in actions {
+                    if ui.button(action.to_string()).clicked() {
+                        choice = Some(MenuChoice::Rule(Rule {
+                            pattern: pattern.clone(),
+                            action,
+                        }));
+                        ui.close_menu();
+                    }
+                }
+            });
+            if ui.button(format!("Exclude {}", pattern)).clicked() {
+                choice = Some(MenuChoice::Exclude(pattern.clone()));
+                ui.close_menu();
+            }
+        }
+        choice
+    }
+
+    fn rules_ui(rules: &mut Vec<Rule>, ui: &mut Ui) {
+        if rules.is_empty() {
+            ui.label("Right click a row's interpretation or path to override it.");
+        }
+        let mut to_remove = None;
+        for (i, rule) in rules.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                    to_remove = Some(i);
+                }
+                ui.label(format!("{}: {}", rule.pattern, rule.action));
+            });
+        }
+        if let Some(i) = to_remove {
+            rules.remove(i);
+        }
+    }
+
+    fn live_ui(
+        live: &mut bool,
+        expiry_filter: &mut ExpiryFilter,
+        anchors: &mut Vec<Anchor>,
+        ui: &mut Ui,
+    ) {
+        ui.horizontal(|ui| {
+            let checkbox = ui.checkbox(live, "Live").on_hover_text(
+                "Follow the current time: past timestamps are colored red, future ones green",
+            );
+            let has_now = anchors
+                .iter()
+                .any(|anchor| anchor.source == AnchorSource::Now);
+            if checkbox.changed() && *live && !has_now {
+                anchors.insert(0, Anchor::new("now", AnchorSource::Now));
+            }
+            if !*live {
+                return;
+            }
+            ui.label("Show:");
+            let within = match *expiry_filter {
+                ExpiryFilter::ExpiringWithin(secs) => secs,
+                _ => 3600,
+            };
+            let text = |filter: ExpiryFilter| match filter {
+                ExpiryFilter::All => "all",
+                ExpiryFilter::Expired => "expired",
+                ExpiryFilter::ExpiringWithin(_) => "expired or expiring within",
+            };
+            egui::ComboBox::from_id_source("expiry filter")
+                .selected_text(text(*expiry_filter))
+                .show_ui(ui, |ui| {
+                    for filter in [
+                        ExpiryFilter::All,
+                        ExpiryFilter::Expired,
+                        ExpiryFilter::ExpiringWithin(within),
+                    ] {
+                        ui.selectable_value(expiry_filter, filter, text(filter));
+                    }
+                });
+            if let ExpiryFilter::ExpiringWithin(secs) = expiry_filter {
+                ui.add(
+                    egui::DragValue::new(secs)
+                        .clamp_range(0..=i64::MAX)
+                        .suffix(" s"),
+                );
+            }
+        });
+    }
+
+    fn gaps_ui(
+        hits: &[Hit],
+        gap_threshold_secs: &mut f64,
+        duration_style: DurationStyle,
+        ui: &mut Ui,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label("Highlight gaps over:");
+            ui.add(
+                egui::DragValue::new(gap_threshold_secs)
+                    .clamp_range(0.0..=f64::MAX)
+                    .suffix(" s"),
+            )
+            .on_hover_text("Δ prev values longer than this are highlighted. 0 disables it.");
+        });
+        egui::CollapsingHeader::new("Interval stats").show(ui, |ui| {
+            let (all, by_pattern) = interval_stats(hits);
+            let Some(all) = all else {
+                ui.label("Needs at least two timestamps.");
+                return;
+            };
+            egui::Grid::new("interval stats")
+                .striped(true)
+                .show(ui, |ui| {
+                    for header in ["Paths", "Intervals", "Min", "Median", "P95", "Max"] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+                    let rows = std::iter::once(("All".to_owned(), all)).chain(
+                        by_pattern
+                            .into_iter()
+                            .map(|(pattern, stats)| (pattern.to_string(), stats)),
+                    );
+                    for (paths, stats) in rows {
+                        let IntervalStats {
+                            count,
+                            min,
+                            median,
+                            p95,
+                            max,
+                        } = stats;
+                        ui.label(paths);
+                        ui.label(count.to_string());
+                        for duration in [min, median, p95, max] {
+                            add_copiable_label(duration_style.format(duration), ui, true);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+
+    fn durations_table_ui(
+        x: &[(JsonPath, Duration)],
+        duration_style: DurationStyle,
+        sort_by: &mut SortBy,
+        ascend: &mut bool,
+        ui: &mut egui::Ui,
+    ) {
+        use egui_extras::{Column, TableBuilder};
+
+        let table = TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::remainder())
+            .min_scrolled_height(0.0);
+        let arrow = if *ascend { " ↗" } else { " ↘" };
+        let (time_arrow, path_arrow) = match sort_by {
+            SortBy::Time => (arrow, ""),
+            SortBy::Path => ("", arrow),
+        };
+        let mut time_clicked = false;
+        let mut path_clicked = false;
+        table
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.strong("Row");
+                });
+                header.col(|ui| {
+                    time_clicked |=
+                        Self::clickable_strong_label(format!("Duration{}", time_arrow), ui);
+                });
+                header.col(|ui| {
+                    path_clicked |=
+                        Self::clickable_strong_label(format!("Path in JSON{}", path_arrow), ui);
+                });
+            })
+            .body(|mut body| {
+                for (row_index, (path, duration)) in x.iter().enumerate() {
+                    body.row(18.0, |mut row| {
+                        row.col(|ui| {
+                            ui.label(row_index.to_string());
+                        });
+                        row.col(|ui| {
+                            add_copiable_label(duration_style.format(*duration), ui, true);
                         });
                         row.col(|ui| {
                             ui.style_mut().wrap = Some(false);
@@ -233,28 +1589,68 @@ impl TemplateApp {
             );
         }
 
-        // Collect dropped files:
-        ctx.input(|i| {
-            dbg!(&i.raw.dropped_files);
-            if !i.raw.dropped_files.is_empty() {
-                dbg!(&i.raw.dropped_files);
-                if let Some(bytes) = i.raw.dropped_files[0].bytes.as_deref() {
-                    println!("some bytes");
-                    if let Ok(file_content) = std::str::from_utf8(bytes) {
-                        println!("valid utf8");
-                        self.json_body = file_content.to_owned();
-                    }
-                }
-                // let y = x.clone();
-                // let v: &[u8] = &y;
-                // // let v = x.into_iter();
+        // Open dropped files, each in its own tab:
+        let dropped_files = ctx.input(|i| i.raw.dropped_files.clone());
+        for dropped in dropped_files {
+            let opened = match (dropped.path, dropped.bytes) {
+                (Some(path), _) => OpenedFile::read(path),
+                (None, Some(bytes)) => String::from_utf8(bytes.to_vec())
+                    .map(|contents| OpenedFile {
+                        name: dropped.name.clone(),
+                        path: None,
+                        contents,
+                    })
+                    .map_err(|_| format!("{} is not valid UTF-8", dropped.name)),
+                (None, None) => continue,
+            };
+            match opened {
+                Ok(file) => self.open_file(file),
+                Err(err) => self.file_status = Some(Err(err)),
+            }
+        }
+    }
 
-                // // let c = String::from_utf8(x.into_iter().collect());
-                // let z = std::str::from_utf8(v);
+    /// A tab per document (with a close button), and the combined view if there are several.
+    fn tabs_ui(documents: &mut Documents, ui: &mut Ui) {
+        let mut to_select = None;
+        let mut to_close = None;
+        ui.horizontal_wrapped(|ui| {
+            for (i, document) in documents.documents().iter().enumerate() {
+                let tab = Tab::Document(i);
+                #[cfg(not(target_arch = "wasm32"))]
+                let name = match document.watch {
+                    Some(_) => format!("{} (watching)", document.name),
+                    None => document.name.clone(),
+                };
+                #[cfg(target_arch = "wasm32")]
+                let name = document.name.clone();
+                let label = ui.selectable_label(documents.tab() == tab, name);
+                if label.clicked() {
+                    to_select = Some(tab);
+                }
+                if let Some(path) = &document.path {
+                    label.on_hover_text(path.display().to_string());
+                }
+                if ui.small_button("✖").on_hover_text("Close").clicked() {
+                    to_close = Some(i);
+                }
+                ui.separator();
+            }
+            if documents.documents().len() >= 2 {
+                let label = ui
+                    .selectable_label(documents.tab() == Tab::Combined, "Combined")
+                    .on_hover_text("All documents in one timeline");
+                if label.clicked() {
+                    to_select = Some(Tab::Combined);
+                }
             }
         });
-
-        // Show dropped files (if any):
+        if let Some(tab) = to_select {
+            documents.select(tab);
+        }
+        if let Some(i) = to_close {
+            documents.close(i);
+        }
     }
 
     fn show_instructions(ctx: &egui::Context, open: &mut bool) {
@@ -263,12 +1659,57 @@ impl TemplateApp {
             .resizable(true)
             .vscroll(false)
             .show(ctx, |ui| {
-                ui.label("Paste or drop any JSON file in the left box. \
+                ui.label("Paste or drop any JSON file in the left box, or open one from the \"File\" menu \
+                (the native app also opens a file given on the command line). \
+                Each file opens in its own tab, and the \"Combined\" tab shows all of them in one timeline, \
+                with the file name as the first part of each path. \
                 Any numeric field that is a valid unix timestamp, will be displayed on the table on the right.\n\n\
+                Protobuf timestamps ({\"seconds\": ..., \"nanos\": ...}) are shown as a single timestamp, \
+                and protobuf durations (e.g. \"3.5s\") are listed under \"Durations\".\n\n\
                 A numeric value is considered a valid unix timestamp if it is between the min and max years.\n\n\
-                The \"Relative\" column displays the time relative to the anchor. \
-                You can set the anchor manually, or by right clicking any timestamp on the table.\n\n\
+                Strings are recognized too, if they hold a number or match one of the string formats \
+                (RFC 3339 by default).\n\n\
+                JSON Web Tokens, pasted on their own or found in string values, are decoded into their header and payload \
+                (the signature isn't checked). Their exp, iat, nbf and auth_time claims are labelled, \
+                and claims that contradict each other (e.g. nbf after exp) are flagged above the table.\n\n\
+                Besides unix seconds, numbers are also tried as the other enabled epoch families \
+                (milliseconds, Windows FILETIME, Excel serials, ...). The first one that lands between the min and max years is shown in the \"Interpretation\" column.\n\n\
+                Each timestamp gets a confidence score, based on its key name (e.g. \"created_at\" vs \"user_id\"), its unit, \
+                its siblings in arrays and how round it is. Use the \"Min confidence\" slider to hide unlikely ones.\n\n\
+                If a value is misread, right click its interpretation or path to pin the right epoch family, \
+                or mark it as not a timestamp, for that path or for all the array items like it.\n\n\
+                All these settings can be saved as a named profile, and exported or imported as a JSON file \
+                to share with others (the native app also accepts `--profile <file>`).\n\n\
+                \"File\" > \"Save annotated\" saves the document with every timestamp replaced by its time and \
+                interpretation, and \"Export table\" saves the table as CSV.\n\n\
+                In the native app, \"File\" > \"Watch for changes\" reloads the file whenever it's rewritten or appended to \
+                (JSON or newline-delimited JSON). Timestamps that appeared in the last reload are highlighted, and fixed anchors stay put.\n\n\
+                \"Clipboard\" > \"Monitor clipboard\" (native app) loads any JSON you copy into the \"Clipboard\" tab, \
+                and keeps the last few payloads in that menu to go back to.\n\n\
+                \"Copy link\" (under \"Share\") makes a link that opens the document, with its years, format, \
+                time zone, anchors and sorting, in the web app.\n\n\
+                Each anchor gets a \"Relative to\" column, displaying the time relative to it. \
+                Pick how these and other durations are written with \"Durations\" (this also applies to the CSV export). \
+                An anchor is either a fixed time, the current time, the earliest or latest timestamp shown, \
+                or the timestamp at a path, which follows that field's value as the document changes. \
+                Add and edit anchors under \"Anchors\", or right click any value on the table to move one there.\n\n\
                 The table can be sorted either by time or path in Json.\n\n\
+                The \"Converter\" goes the other way: type a date, and it shows the date's value in every epoch family. \
+                Click a value to copy it, or right click it to set an anchor there.\n\n\
+                Click a row's number to select its value in the editor.\n\n\
+                The timestamps are highlighted in the editor too: hover one to see its time, and how long it is from each anchor. \
+                \"Format\" pretty-prints the document and \"Minify\" removes its whitespace, both keeping the key order; \
+                \"Sort keys\" sorts every object's keys. A stream of documents (e.g. NDJSON) keeps one per line when minified.\n\n\
+                Double click a human readable time to edit it: press enter, and the value in the JSON is rewritten in its own unit and format, leaving the rest of the text as it was.\n\n\
+                \"Shift timestamps\" moves every timestamp in the open document, by a duration or so the earliest (or an anchor) lands on a date. \
+                Each value keeps its unit, type and format, e.g. Unix milliseconds in a string stay so.\n\n\
+                \"Live\" keeps up with the current time (e.g. for token expiry or cache TTLs): it adds a \"now\" anchor, \
+                colors past timestamps red and future ones green, and can show only the expired ones, \
+                or the ones expiring soon.\n\n\
+                The \"Δ prev\" column displays the time since the previous row, in the current order. \
+                Set \"Highlight gaps over\" to spot unusually long ones, and open \"Interval stats\" for the \
+                min, median, 95th percentile and max interval between consecutive timestamps, overall and per path \
+                (array indices ignored).\n\n\
                 Left click a table cell to copy its content.
                 ")
             });
@@ -277,30 +1718,63 @@ impl TemplateApp {
 
 impl eframe::App for TemplateApp {
     /// Called by the frame work to save state before shutdown.
-    // fn save(&mut self, storage: &mut dyn eframe::Storage) {
-    //     eframe::set_value(storage, eframe::APP_KEY, self);
-    // }
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, self);
+    }
 
     /// Called each time the UI needs repainting, which may be many times per second.
     /// Put your widgets into a `SidePanel`, `TopPanel`, `CentralPanel`, `Window` or `Area`.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        let Self {
-            min_year,
-            max_year,
-            json_body,
-            fmt,
-            anchor,
-            highlighted_path,
-            sort_by,
-            ascend,
-            instruction_open,
-        } = self;
-        Self::show_instructions(ctx, instruction_open);
+        Self::show_instructions(ctx, &mut self.instruction_open);
+        let settings_before = self.settings.clone();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let scanner = Scanner::from_settings(&self.settings);
+            if let Some(reload) = self.documents.poll_watched(&scanner).pop() {
+                self.file_status = Some(reload);
+            }
+            if self
+                .documents
+                .documents()
+                .iter()
+                .any(|document| document.watch.is_some())
+            {
+                ctx.request_repaint_after(std::time::Duration::from_millis(500));
+            }
+            match self.clipboard.poll() {
+                Some(Ok(payload)) => {
+                    self.file_status = Some(Ok("Loaded JSON from the clipboard".to_owned()));
+                    self.show_clipboard_payload(payload);
+                }
+                Some(Err(err)) => self.file_status = Some(Err(err)),
+                None => {}
+            }
+            if self.clipboard.enabled {
+                ctx.request_repaint_after(crate::clipboard::POLL_INTERVAL);
+            }
+        }
+        match self.file_dialogs.poll_opened() {
+            Some(Ok(file)) => self.open_file(file),
+            Some(Err(err)) => self.file_status = Some(Err(err)),
+            None => {}
+        }
 
-        let min_ts = year_to_ts(*min_year).unwrap();
-        let max_ts = year_to_ts(*max_year + 1).unwrap();
-        let predicate = |ts| (ts >= min_ts) && (ts <= max_ts);
-        let parsed_json = serde_json::from_str(json_body);
+        egui::TopBottomPanel::top("menu bar").show(ctx, |ui| {
+            egui::menu::bar(ui, |ui| {
+                ui.menu_button("File", |ui| self.file_menu_ui(ui));
+                #[cfg(not(target_arch = "wasm32"))]
+                ui.menu_button("Clipboard", |ui| self.clipboard_menu_ui(ui));
+                match &self.file_status {
+                    Some(Ok(status)) => {
+                        ui.weak(status);
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, err);
+                    }
+                    None => {}
+                }
+            });
+        });
 
         egui::SidePanel::left("left panel").show(ctx, |ui| {
             ui.heading("JSON-unix-time");
@@ -308,66 +1782,284 @@ impl eframe::App for TemplateApp {
             egui::warn_if_debug_build(ui);
 
             if ui.button("Instructions").clicked() {
-                *instruction_open = true;
+                self.instruction_open = true;
             }
             ui.separator();
+            egui::CollapsingHeader::new("Profiles").show(ui, |ui| {
+                self.profiles_ui(ui);
+            });
+            egui::CollapsingHeader::new("Share")
+                .default_open(self.link_status.is_some())
+                .show(ui, |ui| {
+                    self.share_ui(ui);
+                });
+            let Self {
+                settings,
+                documents,
+                anchors,
+                pending_selection,
+                format_error,
+                editor_hits,
+                new_include,
+                new_exclude,
+                new_format,
+                converter_input,
+                converter_time_zone,
+                shift_form,
+                ..
+            } = self;
             ui.horizontal(|ui| {
                 ui.label("Min year:");
-                ui.add(egui::DragValue::new(min_year).speed(1.0));
+                ui.add(egui::DragValue::new(&mut settings.min_year).speed(1.0));
                 ui.label("Max year:");
-                ui.add(egui::DragValue::new(max_year).speed(1.0));
+                ui.add(egui::DragValue::new(&mut settings.max_year).speed(1.0));
             });
             ui.horizontal(|ui| {
                 ui.label("Datetime format:");
-                ui.text_edit_singleline(fmt);
+                ui.text_edit_singleline(&mut settings.fmt);
             });
             ui.horizontal(|ui| {
-                ui.label("Anchor ts:");
-                ui.add(egui::DragValue::new(anchor).speed(1.0));
-                ui.label(ts_to_str(*anchor, fmt).unwrap_or("N/A".to_owned()));
+                ui.label("Durations:");
+                egui::ComboBox::from_id_source("duration style")
+                    .selected_text(settings.duration_style.example())
+                    .show_ui(ui, |ui| {
+                        for style in DURATION_STYLES {
+                            ui.selectable_value(
+                                &mut settings.duration_style,
+                                *style,
+                                style.example(),
+                            );
+                        }
+                    });
             });
-            ui.separator();
-            ScrollArea::vertical().show(ui, |ui| {
-                egui::TextEdit::multiline(json_body)
-                    .hint_text("Paste your JSON here!")
-                    .desired_width(f32::INFINITY)
-                    .show(ui);
+            ui.horizontal(|ui| {
+                ui.label("Time zone:");
+                Self::time_zone_ui("time zone", &mut settings.time_zone, ui);
             });
-        });
-        egui::CentralPanel::default().show(ctx, |ui| match parsed_json {
-            Ok(parsed_json) => {
-                let mut out = vec![];
-                crawl_json(&parsed_json, JsonPath::new(), &predicate, &mut out);
-                // out.sort_by(|a, b| );
-                match sort_by {
-                    SortBy::Time => out.sort_by_key(|(path, ts)| (*ts, path.0.clone())),
-                    SortBy::Path => {
-                        out.sort_by_key(|(path, _)| path.0.clone())
-                        // let z = out.iter().map(|(path, _)| path);
+            egui::CollapsingHeader::new("Epoch families").show(ui, |ui| {
+                for family in EPOCH_FAMILIES {
+                    let mut enabled = settings.families.contains(family);
+                    if ui.checkbox(&mut enabled, family.name).changed() {
+                        if enabled {
+                            settings.families.push(*family);
+                        } else {
+                            settings.families.retain(|f| f != family);
+                        }
                     }
                 }
-                if !*ascend {
-                    out.reverse();
+            });
+            egui::CollapsingHeader::new("String formats").show(ui, |ui| {
+                ui.label("Strings are parsed with these chrono formats, or \"rfc3339\".");
+                let parse_format = |format: &str| {
+                    if format.is_empty() {
+                        Err("Enter a format, e.g. %d/%m/%Y %H:%M".to_owned())
+                    } else {
+                        Ok(format.to_owned())
+                    }
+                };
+                Self::list_ui(
+                    &mut settings.formats,
+                    new_format,
+                    "e.g. %d/%m/%Y %H:%M",
+                    parse_format,
+                    ui,
+                );
+            });
+            egui::CollapsingHeader::new(format!("Overrides ({})", settings.rules.len())).show(
+                ui,
+                |ui| {
+                    Self::rules_ui(&mut settings.rules, ui);
+                },
+            );
+            egui::CollapsingHeader::new(format!(
+                "Path filters ({})",
+                settings.include.len() + settings.exclude.len()
+            ))
+            .show(ui, |ui| {
+                ui.label("Only include:");
+                Self::list_ui(
+                    &mut settings.include,
+                    new_include,
+                    "e.g. events[*].meta",
+                    str::parse,
+                    ui,
+                );
+                ui.label("Exclude:");
+                Self::list_ui(
+                    &mut settings.exclude,
+                    new_exclude,
+                    "e.g. events[*].meta",
+                    str::parse,
+                    ui,
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Min confidence:");
+                ui.add(egui::Slider::new(&mut settings.min_confidence, 0.0..=1.0));
+            });
+            egui::CollapsingHeader::new("Anchors")
+                .default_open(true)
+                .show(ui, |ui| Self::anchors_ui(anchors, settings, ui));
+            egui::CollapsingHeader::new("Converter").show(ui, |ui| {
+                Self::converter_ui(converter_input, converter_time_zone, settings, anchors, ui)
+            });
+            egui::CollapsingHeader::new("Shift timestamps").show(ui, |ui| {
+                Self::shift_ui(shift_form, documents, settings, anchors, ui)
+            });
+            ui.separator();
+            match documents.active_mut() {
+                Some(document) => {
+                    Self::format_buttons_ui(document, format_error, ui);
+                    ScrollArea::vertical().show(ui, |ui| {
+                        Self::editor_ui(
+                            document,
+                            editor_hits,
+                            pending_selection,
+                            settings,
+                            anchors,
+                            ui,
+                        )
+                    });
+                }
+                None => {
+                    ui.label(format!(
+                        "Showing all {} documents. Select a tab to edit one.",
+                        documents.documents().len()
+                    ));
                 }
-                ScrollArea::horizontal().show(ui, |ui| {
-                    Self::table_ui(&out, fmt, anchor, sort_by, ascend, ui);
-                });
-                // let path_to_open = Some(JsonPath(vec![
-                //     JsonPathPart::Field("field4".to_owned()),
-                //     JsonPathPart::Field("subfield2".to_owned()),
-                // ]));
-                // tree(
-                //     &parsed_json,
-                //     ui,
-                //     "".to_owned(),
-                //     JsonPath::new(),
-                //     path_to_open.as_ref(),
-                // );
             }
-            Err(err) => {
-                ui.label(err.to_string());
+        });
+
+        let Self {
+            settings,
+            documents,
+            anchors,
+            sort_by,
+            ascend,
+            gap_threshold_secs,
+            live,
+            expiry_filter,
+            view,
+            table_error,
+            pending_selection,
+            editor_hits,
+            scan_cache,
+            ..
+        } = self;
+        let scanner = Scanner::from_settings(settings);
+        let found = ScanCache::get(scan_cache, documents, scanner.clone());
+        egui::CentralPanel::default().show(ctx, |ui| {
+            Self::tabs_ui(documents, ui);
+            ui.separator();
+            match found {
+                Ok((mut out, mut durations, messages)) => {
+                    // Documents that failed to parse, and JWTs with inconsistent claims.
+                    for (is_error, message) in messages {
+                        let color = match is_error {
+                            true => ui.visuals().error_fg_color,
+                            false => ui.visuals().warn_fg_color,
+                        };
+                        ui.colored_label(color, message);
+                    }
+                    let now = live.then(Timestamp::now);
+                    if let Some(now) = now {
+                        out.retain(|hit| expiry_filter.keeps(hit.ts, now));
+                    }
+                    *editor_hits = match documents.active() {
+                        Some(_) => out.clone(),
+                        None => vec![],
+                    };
+                    sort_hits(&mut out, *sort_by, *ascend);
+                    match sort_by {
+                        SortBy::Time => durations.sort_by_key(|(path, d)| (*d, path.0.clone())),
+                        SortBy::Path => durations.sort_by_key(|(path, _)| path.0.clone()),
+                    }
+                    if !*ascend {
+                        durations.reverse();
+                    }
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(
+                            view,
+                            View::Timestamps,
+                            format!("Timestamps ({})", out.len()),
+                        );
+                        ui.selectable_value(
+                            view,
+                            View::Durations,
+                            format!("Durations ({})", durations.len()),
+                        );
+                    });
+                    if let Some(err) = table_error {
+                        ui.colored_label(ui.visuals().error_fg_color, err.as_str());
+                    }
+                    ScrollArea::horizontal().show(ui, |ui| match view {
+                        View::Timestamps => {
+                            Self::live_ui(live, expiry_filter, anchors, ui);
+                            Self::gaps_ui(&out, gap_threshold_secs, settings.duration_style, ui);
+                            let highlights = Highlights {
+                                is_new: &|hit| documents.is_new(hit),
+                                gap_threshold: (*gap_threshold_secs > 0.0).then(|| {
+                                    Duration::nanoseconds((*gap_threshold_secs * 1e9) as i64)
+                                }),
+                                now,
+                            };
+                            let action = Self::table_ui(
+                                &out,
+                                &highlights,
+                                settings,
+                                anchors,
+                                sort_by,
+                                ascend,
+                                ui,
+                            );
+                            match action {
+                                Some(TableAction::Edit(hit, text)) => {
+                                    let edit = parse_date(&text, &settings.fmt, settings.time_zone)
+                                        .ok_or(format!("{:?} isn't a date I can read", text))
+                                        .and_then(|ts| documents.edit_hit(&hit, ts));
+                                    *table_error = edit.err();
+                                }
+                                Some(TableAction::Select(hit)) => {
+                                    let located = documents.locate(&hit.path).zip(hit.span);
+                                    match located {
+                                        Some(((i, _), span)) => {
+                                            documents.select(Tab::Document(i));
+                                            *pending_selection = Some(span.range);
+                                            *table_error = None;
+                                            ui.ctx().request_repaint();
+                                        }
+                                        None => {
+                                            *table_error = Some(format!(
+                                            "{} isn't in the text as is, e.g. it's inside a JWT",
+                                            hit.path
+                                        ))
+                                        }
+                                    }
+                                }
+                                None => {}
+                            }
+                        }
+                        View::Durations => Self::durations_table_ui(
+                            &durations,
+                            settings.duration_style,
+                            sort_by,
+                            ascend,
+                            ui,
+                        ),
+                    });
+                }
+                Err(err) => {
+                    editor_hits.clear();
+                    ui.label(err);
+                }
             }
         });
+        if self.live {
+            ctx.request_repaint_after(std::time::Duration::from_secs(1));
+        }
+        if self.settings != settings_before {
+            self.active_profile = None;
+        }
         self.ui_file_drag_and_drop(ctx);
     }
 }
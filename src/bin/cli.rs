@@ -0,0 +1,354 @@
+#![warn(clippy::all, rust_2018_idioms)]
+
+use std::io::Read;
+use std::process::ExitCode;
+
+use chrono::Duration;
+use json_unix_time::{
+    check_years, parse_date, parse_duration, parse_input, shift_text, timestamp_to_str,
+    EpochFamily, FileWatcher, Hit, NormalizeMode, Normalizer, Profile, Scanner, SeenHits, Settings,
+    Shift, Timestamp, EPOCH_FAMILIES,
+};
+
+const USAGE: &str = "\
+Usage: json_unix_time_cli <command> [options] [file]
+
+Reads JSON (or newline-delimited JSON, or a JWT) from the file, or from stdin if none is given.
+JWTs, also inside strings, are decoded into their header and payload.
+
+Commands:
+  scan       Print the timestamps found, one per line: path, value, interpretation, time
+  watch      Like scan, then reload the file whenever it changes and print the new timestamps
+  durations  Print the protobuf durations found (e.g. \"3.5s\"), one per line: path, duration
+  shift      Print the document with every timestamp moved, by --by, or so the earliest
+             (or --from) is at --to. Each value keeps its unit, type and format.
+  normalize  Print the document with every timestamp replaced by a deterministic one, e.g. for
+             snapshot tests. Each value keeps its unit, type and format.
+
+Options:
+  --profile <file>         Start from the settings in a profile
+  --years <min>..<max>     Only look for timestamps in these years
+  --format <fmt>           Output format, e.g. \"%Y-%m-%d %H:%M:%S\"
+  --tz <tz>                Output time zone: UTC, Local, or an offset like +05:30
+  --family <name>          Only try this epoch family (repeatable)
+  --string-format <fmt>    Also parse strings with this format (repeatable)
+  --include <pattern>      Only look inside paths matching the pattern (repeatable)
+  --exclude <pattern>      Skip paths matching the pattern (repeatable)
+  --min-confidence <0..1>  Skip hits scored below this
+  --duration-style <style> How durations are written: clock, compact, iso8601, verbose or millis
+  --by <duration>          shift: how far to move, e.g. \"-1h 30m\", \"2w\" or \"3.5s\"
+  --to <date>              shift: where the earliest timestamp (or --from) lands
+  --from <date>            shift: the time that lands on --to, instead of the earliest
+  --mode <mode>            normalize: fixed (all at --base, the default), sequential (--step
+                           apart, in order) or relative (keeping their offsets from the earliest)
+  --base <date>            normalize: the earliest time, 2000-01-01T00:00:00Z by default
+  --step <duration>        normalize: how far apart sequential times are, 1s by default
+  -h, --help               Print this help";
+
+struct Options {
+    settings: Settings,
+    file: Option<String>,
+    by: Option<Duration>,
+    /// Dates are parsed once the format and time zone are known.
+    from: Option<String>,
+    to: Option<String>,
+    mode: NormalizeMode,
+    base: Option<String>,
+    step: Option<Duration>,
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut settings = Settings::default();
+    let mut families: Vec<EpochFamily> = vec![];
+    let mut formats: Vec<String> = vec![];
+    let mut file = None;
+    let (mut by, mut from, mut to) = (None, None, None);
+    let (mut mode, mut base, mut step) = (NormalizeMode::default(), None, None);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} requires a value", name));
+        match arg.as_str() {
+            "--profile" => settings = Profile::from_file(&value("--profile")?)?.settings,
+            "--years" => {
+                let years = value("--years")?;
+                let (min, max) = years
+                    .split_once("..")
+                    .ok_or(format!("bad --years {:?}, expected e.g. 2020..2030", years))?;
+                settings.min_year = min.parse().map_err(|_| format!("bad year {:?}", min))?;
+                settings.max_year = max.parse().map_err(|_| format!("bad year {:?}", max))?;
+                check_years(settings.min_year, settings.max_year)?;
+            }
+            "--format" => settings.fmt = value("--format")?,
+            "--tz" => settings.time_zone = value("--tz")?.parse()?,
+            "--family" => {
+                let name = value("--family")?;
+                families.push(EpochFamily::by_name(&name).ok_or_else(|| {
+                    let names: Vec<_> = EPOCH_FAMILIES.iter().map(|family| family.name).collect();
+                    format!("unknown family {:?}, expected one of {:?}", name, names)
+                })?);
+            }
+            "--string-format" => formats.push(value("--string-format")?),
+            "--include" => settings.include.push(value("--include")?.parse()?),
+            "--exclude" => settings.exclude.push(value("--exclude")?.parse()?),
+            "--min-confidence" => {
+                let min_confidence = value("--min-confidence")?;
+                settings.min_confidence = min_confidence
+                    .parse()
+                    .map_err(|_| format!("bad --min-confidence {:?}", min_confidence))?;
+            }
+            "--duration-style" => settings.duration_style = value("--duration-style")?.parse()?,
+            "--by" => {
+                let duration = value("--by")?;
+                by = Some(
+                    parse_duration(&duration)
+                        .ok_or(format!("bad --by {:?}, expected e.g. -1h 30m", duration))?,
+                );
+            }
+            "--from" => from = Some(value("--from")?),
+            "--to" => to = Some(value("--to")?),
+            "--mode" => mode = value("--mode")?.parse()?,
+            "--base" => base = Some(value("--base")?),
+            "--step" => {
+                let duration = value("--step")?;
+                step = Some(
+                    parse_duration(&duration)
+                        .ok_or(format!("bad --step {:?}, expected e.g. 1m", duration))?,
+                );
+            }
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("unexpected option {:?}", arg))
+            }
+            _ if file.is_none() => file = Some(arg),
+            _ => return Err(format!("unexpected argument {:?}", arg)),
+        }
+    }
+    if !families.is_empty() {
+        settings.families = families;
+    }
+    settings.formats.extend(formats);
+    Ok(Options {
+        settings,
+        file: file.filter(|file| file != "-"),
+        by,
+        from,
+        to,
+        mode,
+        base,
+        step,
+    })
+}
+
+/// Parses JSON, NDJSON or a JWT, and reports the JWTs' inconsistent claims on stderr.
+fn parse_value(text: &str) -> Result<serde_json::Value, String> {
+    let input = parse_input(text).map_err(|err| format!("invalid JSON: {}", err))?;
+    for problem in input.problems() {
+        eprintln!("# {}", problem);
+    }
+    Ok(input.value)
+}
+
+fn read_text(file: Option<&str>) -> Result<String, String> {
+    Ok(match file {
+        Some(path) => {
+            std::fs::read_to_string(path).map_err(|err| format!("can't read {}: {}", path, err))?
+        }
+        None => {
+            let mut text = String::new();
+            std::io::stdin()
+                .read_to_string(&mut text)
+                .map_err(|err| format!("can't read stdin: {}", err))?;
+            text
+        }
+    })
+}
+
+fn read_json(file: Option<&str>) -> Result<serde_json::Value, String> {
+    parse_value(&read_text(file)?)
+}
+
+fn print_hit(hit: &Hit, settings: &Settings) {
+    let time = timestamp_to_str(hit.ts, &settings.fmt, settings.time_zone)
+        .unwrap_or_else(|| "out of range".to_owned());
+    println!(
+        "{}\t{}\t{}\t{}",
+        hit.path, hit.raw, hit.interpretation, time
+    );
+}
+
+fn scan(options: &Options) -> Result<(), String> {
+    let value = read_json(options.file.as_deref())?;
+    let settings = &options.settings;
+    for hit in Scanner::from_settings(settings).scan(&value) {
+        print_hit(&hit, settings);
+    }
+    Ok(())
+}
+
+fn durations(options: &Options) -> Result<(), String> {
+    let value = read_json(options.file.as_deref())?;
+    let settings = &options.settings;
+    for (path, duration) in Scanner::from_settings(settings).durations(&value) {
+        println!("{}\t{}", path, settings.duration_style.format(duration));
+    }
+    Ok(())
+}
+
+/// Parses a date option in the output format or a common one, read in the output time zone.
+fn parse_date_option(name: &str, date: &str, settings: &Settings) -> Result<Timestamp, String> {
+    parse_date(date, &settings.fmt, settings.time_zone)
+        .ok_or(format!("bad {} {:?}, expected a date", name, date))
+}
+
+/// Prints a rewritten document, ending in a newline.
+fn print_document(text: &str) {
+    print!("{}", text);
+    if !text.ends_with('\n') {
+        println!();
+    }
+}
+
+fn shift(options: &Options) -> Result<(), String> {
+    let settings = &options.settings;
+    let date = |name: &str, date: &str| parse_date_option(name, date, settings);
+    let shift = match (options.by, &options.from, &options.to) {
+        (Some(by), None, None) => Shift::By(by),
+        (None, from, Some(to)) => Shift::Rebase {
+            from: from
+                .as_deref()
+                .map(|from| date("--from", from))
+                .transpose()?,
+            to: date("--to", to)?,
+        },
+        _ => return Err("shift requires either --by, or --to and maybe --from".to_owned()),
+    };
+    let text = read_text(options.file.as_deref())?;
+    print_document(&shift_text(
+        &text,
+        &Scanner::from_settings(settings),
+        shift,
+    )?);
+    Ok(())
+}
+
+fn normalize(options: &Options) -> Result<(), String> {
+    let settings = &options.settings;
+    let mut normalizer = Normalizer::new(options.mode);
+    if let Some(base) = &options.base {
+        normalizer = normalizer.base(parse_date_option("--base", base, settings)?);
+    }
+    if let Some(step) = options.step {
+        normalizer = normalizer.step(step);
+    }
+    let text = read_text(options.file.as_deref())?;
+    print_document(&normalizer.normalize_text(&text, &Scanner::from_settings(settings))?);
+    Ok(())
+}
+
+/// Prints the timestamps, then the new ones whenever the file changes, until interrupted.
+fn watch(options: &Options) -> Result<(), String> {
+    let file = options.file.as_deref().ok_or("watch requires a file")?;
+    let settings = &options.settings;
+    let scanner = Scanner::from_settings(settings);
+    let mut watcher = FileWatcher::new(file.into());
+    let mut seen = SeenHits::default();
+    let hits = scanner.scan(&read_json(Some(file))?);
+    hits.iter().for_each(|hit| print_hit(hit, settings));
+    seen.update(&hits);
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        let Some(contents) = watcher.poll() else {
+            continue;
+        };
+        // The file may be mid-write, so errors are reported and the next change retried.
+        match contents.and_then(|contents| parse_value(&contents)) {
+            Ok(value) => {
+                let hits = scanner.scan(&value);
+                seen.update(&hits);
+                eprintln!("# reloaded {}: {} new timestamps", file, seen.new_count());
+                for hit in hits.iter().filter(|hit| seen.is_new(hit)) {
+                    print_hit(hit, settings);
+                }
+            }
+            Err(err) => eprintln!("# {}", err),
+        }
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut args = std::env::args().skip(1);
+    let command = args.next().unwrap_or_default();
+    match command.as_str() {
+        "scan" => scan(&parse_options(args)?),
+        "watch" => watch(&parse_options(args)?),
+        "durations" => durations(&parse_options(args)?),
+        "shift" => shift(&parse_options(args)?),
+        "normalize" => normalize(&parse_options(args)?),
+        "-h" | "--help" | "help" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        "" => Err("missing command".to_owned()),
+        _ => Err(format!("unknown command {:?}", command)),
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use json_unix_time::DurationStyle;
+
+    #[test]
+    fn test_parse_options() {
+        let args = [
+            "--years",
+            "2000..2010",
+            "--family",
+            "Unix milliseconds",
+            "--exclude",
+            "meta",
+            "--duration-style",
+            "compact",
+            "--by",
+            "-1h 30m",
+            "--mode",
+            "sequential",
+            "data.json",
+        ];
+        let options = parse_options(args.into_iter().map(str::to_owned)).unwrap();
+        assert_eq!(
+            (options.settings.min_year, options.settings.max_year),
+            (2000, 2010)
+        );
+        assert_eq!(options.settings.families.len(), 1);
+        assert_eq!(options.settings.exclude[0].to_string(), "meta");
+        assert_eq!(options.settings.duration_style, DurationStyle::Compact);
+        assert_eq!(options.by, Some(Duration::minutes(-90)));
+        assert_eq!(options.mode, NormalizeMode::Sequential);
+        assert_eq!(options.file.as_deref(), Some("data.json"));
+
+        for bad in [
+            "--years 2000",
+            "--family Martian",
+            "--bogus",
+            "--duration-style long",
+            "--by soon",
+            "--mode random",
+            "a.json b.json",
+        ] {
+            assert!(
+                parse_options(bad.split(' ').map(str::to_owned)).is_err(),
+                "{}",
+                bad
+            );
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::json_crawl::parse_json_stream;
+
+/// How often the clipboard is read while monitoring it.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Whether copied text looks like something to load: a JSON object or array, or a stream of them.
+/// Bare numbers and strings are valid JSON too, but are far more often copied for other reasons.
+pub fn is_json_payload(text: &str) -> bool {
+    let text = text.trim_start();
+    (text.starts_with('{') || text.starts_with('[')) && parse_json_stream(text).is_ok()
+}
+
+/// Watches the system clipboard for JSON, and remembers the last payloads captured.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ClipboardMonitor {
+    /// Off at every launch, so the clipboard is only read when asked for in this session.
+    #[serde(skip)]
+    pub enabled: bool,
+    /// How many payloads the history keeps.
+    pub history_len: usize,
+    /// Most recent first.
+    #[serde(skip)]
+    history: VecDeque<String>,
+    /// The clipboard's text at the last poll, so each copy is only captured once.
+    #[serde(skip)]
+    last_text: Option<String>,
+    #[serde(skip)]
+    last_poll: Option<Instant>,
+    #[serde(skip)]
+    clipboard: Option<arboard::Clipboard>,
+}
+
+impl Default for ClipboardMonitor {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            history_len: 10,
+            history: VecDeque::new(),
+            last_text: None,
+            last_poll: None,
+            clipboard: None,
+        }
+    }
+}
+
+impl ClipboardMonitor {
+    pub fn history(&self) -> &VecDeque<String> {
+        &self.history
+    }
+
+    /// Adds the payload to the front of the history, moving it there if it's already in it.
+    pub fn record(&mut self, payload: String) {
+        self.history.retain(|old| *old != payload);
+        self.history.push_front(payload);
+        self.history.truncate(self.history_len.max(1));
+    }
+
+    /// The clipboard's contents, if JSON was copied since the last poll. The clipboard is read
+    /// at most every `POLL_INTERVAL`, however often this is called.
+    pub fn poll(&mut self) -> Option<Result<String, String>> {
+        if !self.enabled {
+            return None;
+        }
+        let now = Instant::now();
+        if self
+            .last_poll
+            .map_or(false, |last| now - last < POLL_INTERVAL)
+        {
+            return None;
+        }
+        self.last_poll = Some(now);
+        if self.clipboard.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.clipboard = Some(clipboard),
+                Err(err) => {
+                    self.enabled = false;
+                    return Some(Err(format!("can't access the clipboard: {}", err)));
+                }
+            }
+        }
+        // Fails when the clipboard is empty or holds an image, neither of which is interesting.
+        let text = self.clipboard.as_mut()?.get_text().ok()?;
+        if self.last_text.as_ref() == Some(&text) {
+            return None;
+        }
+        self.last_text = Some(text.clone());
+        if !is_json_payload(&text) {
+            return None;
+        }
+        self.record(text.clone());
+        Some(Ok(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clipboard_history() {
+        assert!(is_json_payload(r#" {"t": 1692694500}"#));
+        assert!(is_json_payload("{\"t\": 1}\n{\"t\": 2}\n"));
+        assert!(!is_json_payload("1692694500"));
+        assert!(!is_json_payload("{not json"));
+
+        let mut monitor = ClipboardMonitor {
+            history_len: 2,
+            ..Default::default()
+        };
+        monitor.record("[1]".to_owned());
+        monitor.record("[2]".to_owned());
+        monitor.record("[1]".to_owned());
+        monitor.record("[3]".to_owned());
+        assert_eq!(monitor.history(), &["[3]", "[1]"]);
+    }
+}
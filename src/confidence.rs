@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::datetime::{EpochFamily, NANOS_PER_DAY, NANOS_PER_SEC};
+use crate::json_crawl::{Hit, Interpretation, JsonPath, JsonPathPart};
+
+/// The lowercase words of a snake_case, kebab-case or camelCase key, e.g. `created`, `at` for
+/// `createdAt` and `http`, `server`, `id` for `HTTPServerId`.
+fn key_words(key: &str) -> Vec<String> {
+    let chars: Vec<char> = key.chars().collect();
+    let mut words = vec![];
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            words.push(std::mem::take(&mut word));
+            continue;
+        }
+        let previous = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1);
+        let starts_word = c.is_uppercase()
+            && match previous {
+                Some(previous) if previous.is_lowercase() || previous.is_ascii_digit() => true,
+                // The last capital of an acronym starts the next word: `HTTPServer`.
+                Some(previous) if previous.is_uppercase() => {
+                    next.map_or(false, |c| c.is_lowercase())
+                }
+                _ => false,
+            };
+        if starts_word {
+            words.push(std::mem::take(&mut word));
+        }
+        word.extend(c.to_lowercase());
+    }
+    words.push(word);
+    words.retain(|word| !word.is_empty());
+    words
+}
+
+fn looks_like_time_key(words: &[String]) -> bool {
+    words.last().map_or(false, |word| word == "at")
+        || words.iter().any(|word| {
+            [
+                "ts",
+                "exp",
+                "iat",
+                "nbf",
+                "time",
+                "timestamp",
+                "date",
+                "datetime",
+                "expires",
+                "created",
+                "updated",
+                "deadline",
+            ]
+            .contains(&word.as_str())
+        })
+}
+
+fn looks_like_non_time_key(words: &[String]) -> bool {
+    words.iter().any(|word| {
+        [
+            "id", "count", "size", "bytes", "price", "amount", "len", "length", "port",
+        ]
+        .contains(&word.as_str())
+    })
+}
+
+/// The unit a key name spells out, e.g. 1_000_000 for `duration_ms` or `createdMillis`.
+fn unit_from_key(key: &str) -> Option<i64> {
+    let lower = key.to_lowercase();
+    let has_suffix = |suffixes: &[&str]| suffixes.iter().any(|s| lower.ends_with(s));
+    if has_suffix(&["_ms", "millis", "milliseconds"]) {
+        Some(1_000_000)
+    } else if has_suffix(&["_us", "micros", "microseconds"]) {
+        Some(1_000)
+    } else if has_suffix(&["_ns", "nanos", "nanoseconds"]) {
+        Some(1)
+    } else if has_suffix(&["_s", "_sec", "_secs", "seconds"]) {
+        Some(NANOS_PER_SEC)
+    } else {
+        None
+    }
+}
+
+/// Unix seconds and milliseconds are by far the most common, everything else needs more evidence.
+fn family_prior(family: &EpochFamily) -> f32 {
+    match (family.epoch, family.unit_nanos) {
+        (0, unit) if unit >= 1_000_000 => 0.0,
+        (0, _) => -0.05,
+        (_, NANOS_PER_DAY) => -0.25,
+        _ => -0.15,
+    }
+}
+
+/// Real timestamps are rarely round beyond their usual resolution (whole seconds),
+/// while ids, sizes and prices often are.
+fn round_number_penalty(raw: &str, family: &EpochFamily) -> f32 {
+    if raw.contains(['.', 'e', 'E']) {
+        return 0.0;
+    }
+    let trailing_zeros = raw.bytes().rev().take_while(|b| *b == b'0').count() as i32;
+    let mut usual_zeros = 0;
+    let mut unit = family.unit_nanos;
+    while unit > 0 && unit * 10 <= NANOS_PER_SEC {
+        unit *= 10;
+        usual_zeros += 1;
+    }
+    let extra_zeros = trailing_zeros - usual_zeros;
+    if extra_zeros >= 3 {
+        (0.1 * (extra_zeros - 2) as f32).min(0.3)
+    } else {
+        0.0
+    }
+}
+
+/// For array elements: do the siblings agree that this is a timestamp, and of the same kind?
+fn sibling_adjustment(
+    hit: &Hit,
+    root: &Value,
+    interpretations_by_parent: &HashMap<JsonPath, Vec<Interpretation>>,
+) -> f32 {
+    if !matches!(hit.path.0.last(), Some(JsonPathPart::Index(_))) {
+        return 0.0;
+    }
+    let Some(parent) = hit.path.parent() else {
+        return 0.0;
+    };
+    let Some(Value::Array(siblings)) = parent.get(root) else {
+        return 0.0;
+    };
+    let Some(interpretations) = interpretations_by_parent.get(&parent) else {
+        return 0.0;
+    };
+    let agreeing = interpretations
+        .iter()
+        .filter(|interpretation| **interpretation == hit.interpretation)
+        .count();
+    if siblings.len() >= 2 && agreeing == siblings.len() {
+        0.1
+    } else if agreeing * 2 < siblings.len() {
+        -0.1
+    } else {
+        0.0
+    }
+}
+
+/// Sets the confidence of every hit, using the key names, the unit, the siblings and
+/// how round the value is.
+pub fn score_hits(root: &Value, hits: &mut [Hit]) {
+    let mut interpretations_by_parent: HashMap<JsonPath, Vec<Interpretation>> = HashMap::new();
+    for hit in hits.iter() {
+        if let Some(parent) = hit.path.parent() {
+            interpretations_by_parent
+                .entry(parent)
+                .or_default()
+                .push(hit.interpretation.clone());
+        }
+    }
+    let scores: Vec<f32> = hits
+        .iter()
+        .map(|hit| score_hit(hit, root, &interpretations_by_parent))
+        .collect();
+    for (hit, score) in hits.iter_mut().zip(scores) {
+        hit.confidence = score;
+    }
+}
+
+fn score_hit(
+    hit: &Hit,
+    root: &Value,
+    interpretations_by_parent: &HashMap<JsonPath, Vec<Interpretation>>,
+) -> f32 {
+    if hit.pinned {
+        return 1.0;
+    }
+    let mut score = match &hit.interpretation {
+        // These have a shape that's hard to hit by accident.
+        Interpretation::ProtoTimestamp | Interpretation::Format(_) => 0.9,
+        Interpretation::Epoch(family) => {
+            let mut score = 0.5 + family_prior(family);
+            score -= round_number_penalty(hit.raw.trim_matches('"'), family);
+            match hit.path.last_field().and_then(unit_from_key) {
+                Some(unit) if unit == family.unit_nanos => score += 0.1,
+                Some(_) => score -= 0.2,
+                None => {}
+            }
+            score
+        }
+    };
+    let words = hit.path.last_field().map(key_words).unwrap_or_default();
+    // Checked first, so `created_count` isn't a time.
+    if looks_like_non_time_key(&words) {
+        score -= 0.3;
+    } else if looks_like_time_key(&words) {
+        score += 0.3;
+    }
+    score += sibling_adjustment(hit, root, interpretations_by_parent);
+    score.clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime::EPOCH_FAMILIES;
+    use crate::json_crawl::crawl_json;
+
+    #[test]
+    fn test_score_hits() {
+        let data = r#"
+        {
+            "created_at": 1692694512,
+            "user_id": 1692694512,
+            "timeout_count": 1692694512,
+            "runtime_bytes": 1692694512,
+            "candidate_id": 1692694512,
+            "validated": 1692694512,
+            "updatedAt": 1692694512,
+            "round": 1692000000,
+            "events": [1692694512, 1692694513, 1692694514],
+            "mixed": [1692694512, 1, 2, 3]
+        }"#;
+        let value = serde_json::from_str(data).unwrap();
+        let predicate = |ts| (1_600_000_000..1_800_000_000).contains(&ts);
+        let mut hits = vec![];
+        crawl_json(
+            &value,
+            JsonPath::new(),
+            &EPOCH_FAMILIES[..1],
+            &[],
+            &[],
+            &predicate,
+            &mut hits,
+        );
+        score_hits(&value, &mut hits);
+        let score = |path: &str| {
+            hits.iter()
+                .find(|hit| hit.path.to_string() == path)
+                .unwrap()
+                .confidence
+        };
+        assert!((score("created_at") - 0.8).abs() < 1e-6);
+        assert!((score("user_id") - 0.2).abs() < 1e-6);
+        assert!((score("updatedAt") - 0.8).abs() < 1e-6);
+        for key in ["timeout_count", "runtime_bytes", "candidate_id"] {
+            assert!((score(key) - 0.2).abs() < 1e-6, "{}", key);
+        }
+        assert!((score("validated") - 0.5).abs() < 1e-6);
+        assert_eq!(
+            key_words("HTTPServer_createdAt2"),
+            ["http", "server", "created", "at2"]
+        );
+        assert!(score("round") < 0.5);
+        assert!(score("events[0]") > score("mixed[0]"));
+    }
+}
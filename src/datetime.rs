@@ -1,4 +1,239 @@
-use chrono::{DateTime, TimeZone, Utc};
+use std::fmt::Display;
+use std::str::FromStr;
+
+use chrono::{
+    DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc,
+};
+
+pub const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+/// A point in time, as whole seconds since the unix epoch plus a non-negative
+/// sub-second part (the same split `google.protobuf.Timestamp` uses).
+#[derive(
+    Clone, Copy, PartialEq, Eq, Hash, Debug, PartialOrd, Ord, serde::Deserialize, serde::Serialize,
+)]
+#[serde(try_from = "(i64, u32)", into = "(i64, u32)")]
+pub struct Timestamp {
+    pub secs: i64,
+    pub nanos: u32,
+}
+
+impl TryFrom<(i64, u32)> for Timestamp {
+    type Error = String;
+
+    fn try_from((secs, nanos): (i64, u32)) -> Result<Self, Self::Error> {
+        Timestamp::new(secs, nanos).ok_or(format!("{} nanoseconds is over a second", nanos))
+    }
+}
+
+impl From<Timestamp> for (i64, u32) {
+    fn from(ts: Timestamp) -> Self {
+        (ts.secs, ts.nanos)
+    }
+}
+
+impl Timestamp {
+    pub fn from_secs(secs: i64) -> Self {
+        Timestamp { secs, nanos: 0 }
+    }
+    pub fn new(secs: i64, nanos: u32) -> Option<Self> {
+        if i64::from(nanos) < NANOS_PER_SEC {
+            Some(Timestamp { secs, nanos })
+        } else {
+            None
+        }
+    }
+    fn from_nanos(nanos: i128) -> Option<Self> {
+        let secs = i64::try_from(nanos.div_euclid(NANOS_PER_SEC.into())).ok()?;
+        let nanos = nanos.rem_euclid(NANOS_PER_SEC.into()) as u32;
+        Some(Timestamp { secs, nanos })
+    }
+    pub fn from_datetime<T: TimeZone>(datetime: DateTime<T>) -> Self {
+        // chrono represents leap seconds as nanos past 1e9, fold them into the second.
+        let nanos = datetime
+            .timestamp_subsec_nanos()
+            .min(NANOS_PER_SEC as u32 - 1);
+        Timestamp {
+            secs: datetime.timestamp(),
+            nanos,
+        }
+    }
+    pub fn now() -> Self {
+        Self::from_datetime(Utc::now())
+    }
+    /// How long after `earlier` this is (negative if it's before), or `None` if that's longer
+    /// than a `Duration` can hold (about 292 million years).
+    pub fn since(self, earlier: Timestamp) -> Option<Duration> {
+        let secs = self.secs.checked_sub(earlier.secs)?;
+        // `Duration::seconds` panics past its range, milliseconds can't.
+        Duration::milliseconds(secs.checked_mul(1000)?).checked_add(&Duration::nanoseconds(
+            i64::from(self.nanos) - i64::from(earlier.nanos),
+        ))
+    }
+    pub fn to_datetime(self) -> Option<DateTime<Utc>> {
+        Utc.timestamp_opt(self.secs, self.nanos).earliest()
+    }
+    /// `duration` later (earlier if it's negative), or `None` if that's out of range.
+    pub fn checked_add(self, duration: Duration) -> Option<Self> {
+        let secs = duration.num_seconds();
+        let subsec_nanos = (duration - Duration::seconds(secs)).num_nanoseconds()?;
+        let nanos = (i128::from(self.secs) + i128::from(secs)) * i128::from(NANOS_PER_SEC)
+            + i128::from(self.nanos)
+            + i128::from(subsec_nanos);
+        Self::from_nanos(nanos)
+    }
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.secs, self.nanos) {
+            (secs, 0) => write!(f, "{}", secs),
+            (secs, nanos) if secs < 0 => {
+                // -1s + 1ns is -0.999999999, not -1.000000001
+                let nanos = NANOS_PER_SEC - i64::from(nanos);
+                write!(f, "-{}.{:09}", -(secs + 1), nanos)
+            }
+            (secs, nanos) => write!(f, "{}.{:09}", secs, nanos),
+        }
+    }
+}
+
+/// A way of counting time: some number of `unit_nanos` long ticks since an epoch.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct EpochFamily {
+    pub name: &'static str,
+    /// The family's epoch, in seconds since the unix epoch.
+    pub epoch: i64,
+    pub unit_nanos: i64,
+}
+
+pub const NANOS_PER_DAY: i64 = 86_400 * NANOS_PER_SEC;
+/// GPS time doesn't observe leap seconds, and has drifted 18 seconds ahead of UTC since 2017.
+const GPS_LEAP_SECONDS: i64 = 18;
+
+/// All the families `crawl_json` knows about, in the order they are tried.
+pub const EPOCH_FAMILIES: &[EpochFamily] = &[
+    EpochFamily {
+        name: "Unix seconds",
+        epoch: 0,
+        unit_nanos: NANOS_PER_SEC,
+    },
+    EpochFamily {
+        name: "Unix milliseconds",
+        epoch: 0,
+        unit_nanos: 1_000_000,
+    },
+    EpochFamily {
+        name: "Unix microseconds",
+        epoch: 0,
+        unit_nanos: 1_000,
+    },
+    EpochFamily {
+        name: "Unix nanoseconds",
+        epoch: 0,
+        unit_nanos: 1,
+    },
+    EpochFamily {
+        name: "Apple/Cocoa seconds",
+        epoch: 978_307_200, // 2001-01-01
+        unit_nanos: NANOS_PER_SEC,
+    },
+    EpochFamily {
+        name: "GPS seconds",
+        epoch: 315_964_800 - GPS_LEAP_SECONDS, // 1980-01-06
+        unit_nanos: NANOS_PER_SEC,
+    },
+    EpochFamily {
+        name: "Windows FILETIME / LDAP",
+        epoch: -11_644_473_600, // 1601-01-01
+        unit_nanos: 100,
+    },
+    EpochFamily {
+        name: ".NET ticks",
+        epoch: -62_135_596_800, // 0001-01-01
+        unit_nanos: 100,
+    },
+    EpochFamily {
+        // Excel pretends 1900 was a leap year, so serials from March 1900 on count from 1899-12-30.
+        name: "Excel serial (1900)",
+        epoch: -2_209_161_600,
+        unit_nanos: NANOS_PER_DAY,
+    },
+    EpochFamily {
+        name: "Excel serial (1904)",
+        epoch: -2_082_844_800, // 1904-01-01
+        unit_nanos: NANOS_PER_DAY,
+    },
+];
+
+impl EpochFamily {
+    pub fn by_name(name: &str) -> Option<Self> {
+        EPOCH_FAMILIES
+            .iter()
+            .find(|family| family.name == name)
+            .copied()
+    }
+    fn value_to_nanos(self, value: i64) -> Option<i128> {
+        let epoch = i128::from(self.epoch) * i128::from(NANOS_PER_SEC);
+        let offset = i128::from(value).checked_mul(self.unit_nanos.into())?;
+        epoch.checked_add(offset)
+    }
+    pub fn timestamp_from_int(&self, value: i64) -> Option<Timestamp> {
+        Timestamp::from_nanos(self.value_to_nanos(value)?)
+    }
+    /// The family's value for `ts`: an integer if it's a whole number of units, a decimal
+    /// otherwise. The inverse of `timestamp_from_int` / `timestamp_from_float`.
+    pub fn value_of(&self, ts: Timestamp) -> Option<String> {
+        let nanos = i128::from(ts.secs)
+            .checked_mul(NANOS_PER_SEC.into())?
+            .checked_add(ts.nanos.into())?;
+        let since_epoch = nanos.checked_sub(i128::from(self.epoch) * i128::from(NANOS_PER_SEC))?;
+        let unit = i128::from(self.unit_nanos);
+        let sign = if since_epoch < 0 { "-" } else { "" };
+        let (whole, rem) = (since_epoch.abs() / unit, since_epoch.abs() % unit);
+        if rem == 0 {
+            return Some(format!("{}{}", sign, whole));
+        }
+        // 12 digits are exact for units down to nanoseconds, and plenty for longer ones like days.
+        const SCALE: i128 = 1_000_000_000_000;
+        let frac = (rem * SCALE + unit / 2) / unit;
+        let (whole, frac) = if frac == SCALE {
+            (whole + 1, 0)
+        } else {
+            (whole, frac)
+        };
+        let frac = format!("{:012}", frac);
+        Some(
+            format!("{}{}.{}", sign, whole, frac.trim_end_matches('0'))
+                .trim_end_matches('.')
+                .to_owned(),
+        )
+    }
+    pub fn timestamp_from_float(&self, value: f64) -> Option<Timestamp> {
+        // Converting the whole part exactly keeps the precision of large values like Excel serials.
+        let whole = value.trunc();
+        if !whole.is_finite() || whole.abs() >= i64::MAX as f64 {
+            return None;
+        }
+        let frac_nanos = ((value - whole) * self.unit_nanos as f64).round() as i128;
+        Timestamp::from_nanos(self.value_to_nanos(whole as i64)? + frac_nanos)
+    }
+}
+
+/// Families are stored by name, so only the ones in `EPOCH_FAMILIES` round-trip.
+impl serde::Serialize for EpochFamily {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for EpochFamily {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = <String as serde::Deserialize>::deserialize(deserializer)?;
+        EpochFamily::by_name(&name)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown epoch family {:?}", name)))
+    }
+}
 
 fn year_to_datetime(year: i32) -> Option<DateTime<Utc>> {
     Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0).earliest()
@@ -10,10 +245,454 @@ pub fn year_to_ts(year: i32) -> Option<i64> {
     Some(datetime.timestamp())
 }
 
-pub fn ts_to_str(ts: i64, fmt: &str) -> Option<String> {
-    let datetime_utc = Utc.timestamp_opt(ts, 0).earliest()?;
+/// The time zone timestamps are displayed in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Tz {
+    #[default]
+    Utc,
+    Local,
+    /// Seconds east of UTC.
+    Fixed(i32),
+}
+
+impl Display for Tz {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tz::Utc => write!(f, "UTC"),
+            Tz::Local => write!(f, "Local"),
+            Tz::Fixed(offset) => {
+                let sign = if *offset < 0 { '-' } else { '+' };
+                let minutes = offset.abs() / 60;
+                write!(f, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+            }
+        }
+    }
+}
+
+impl FromStr for Tz {
+    type Err = String;
+
+    /// Accepts "UTC", "Local", or an offset like "+05:30" or "-08".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || {
+            format!(
+                "expected \"UTC\", \"Local\" or an offset like \"+05:30\", got {:?}",
+                s
+            )
+        };
+        match s {
+            "UTC" | "Z" => return Ok(Tz::Utc),
+            "Local" => return Ok(Tz::Local),
+            _ => {}
+        }
+        let (sign, rest) = match s.split_at(s.len().min(1)) {
+            ("+", rest) => (1, rest),
+            ("-", rest) => (-1, rest),
+            _ => return Err(err()),
+        };
+        let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+        let hours: i32 = hours.parse().map_err(|_| err())?;
+        let minutes: i32 = minutes.parse().map_err(|_| err())?;
+        if !(0..24).contains(&hours) || !(0..60).contains(&minutes) {
+            return Err(err());
+        }
+        Ok(Tz::Fixed(sign * (hours * 3600 + minutes * 60)))
+    }
+}
+
+impl serde::Serialize for Tz {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Tz {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <String as serde::Deserialize>::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+pub fn ts_to_str(ts: i64, fmt: &str, tz: Tz) -> Option<String> {
+    timestamp_to_str(Timestamp::from_secs(ts), fmt, tz)
+}
+
+pub fn timestamp_to_str(ts: Timestamp, fmt: &str, tz: Tz) -> Option<String> {
+    let datetime_utc = ts.to_datetime()?;
     // You can format the DateTime in a human-readable way
-    Some(datetime_utc.format(fmt).to_string())
+    Some(match tz {
+        Tz::Utc => datetime_utc.format(fmt).to_string(),
+        Tz::Local => datetime_utc.with_timezone(&Local).format(fmt).to_string(),
+        Tz::Fixed(offset) => {
+            let offset = FixedOffset::east_opt(offset)?;
+            datetime_utc.with_timezone(&offset).format(fmt).to_string()
+        }
+    })
+}
+
+/// Parses a date string with a chrono format string, or "rfc3339" for RFC 3339 / ISO 8601.
+/// Formats without a time zone are read as UTC, and formats without a time as midnight.
+pub fn parse_formatted(s: &str, format: &str) -> Option<Timestamp> {
+    if format == "rfc3339" {
+        return DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(Timestamp::from_datetime);
+    }
+    if let Ok(datetime) = DateTime::parse_from_str(s, format) {
+        return Some(Timestamp::from_datetime(datetime));
+    }
+    let naive = match NaiveDateTime::parse_from_str(s, format) {
+        Ok(naive) => naive,
+        Err(_) => NaiveDate::parse_from_str(s, format)
+            .ok()?
+            .and_hms_opt(0, 0, 0)?,
+    };
+    Some(Timestamp::from_datetime(Utc.from_utc_datetime(&naive)))
+}
+
+/// Writes `ts` in `format` (see `parse_formatted`) the way `original` is written in it: with the
+/// same UTC offset, and for RFC 3339 the same `Z` and number of fractional digits.
+pub fn format_like(ts: Timestamp, original: &str, format: &str) -> Option<String> {
+    let datetime = ts.to_datetime()?;
+    if format == "rfc3339" {
+        let offset = *DateTime::parse_from_rfc3339(original).ok()?.offset();
+        let digits = original.split_once('.').map_or(0, |(_, fraction)| {
+            fraction.bytes().take_while(u8::is_ascii_digit).count()
+        });
+        let seconds_format = match digits {
+            0 => SecondsFormat::Secs,
+            1..=3 => SecondsFormat::Millis,
+            4..=6 => SecondsFormat::Micros,
+            _ => SecondsFormat::Nanos,
+        };
+        let use_z = original.ends_with(['Z', 'z']);
+        return Some(
+            datetime
+                .with_timezone(&offset)
+                .to_rfc3339_opts(seconds_format, use_z),
+        );
+    }
+    Some(
+        match DateTime::parse_from_str(original, format) {
+            Ok(parsed) => datetime.with_timezone(parsed.offset()).format(format),
+            Err(_) => datetime.fixed_offset().format(format),
+        }
+        .to_string(),
+    )
+}
+
+/// Formats `parse_date` tries after the user's, most specific first.
+const COMMON_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d",
+    "%Y/%m/%d %H:%M:%S%.f",
+    "%Y/%m/%d",
+    "%d %b %Y %H:%M:%S%.f",
+    "%d %b %Y",
+    "%b %d %Y %H:%M:%S%.f",
+    "%b %d, %Y",
+    "%a %b %e %H:%M:%S%.f %Y",
+];
+
+impl Tz {
+    /// The time a wall clock in this time zone shows. Ambiguous times (when clocks go back)
+    /// resolve to the earlier one.
+    pub fn timestamp_from_local(self, naive: NaiveDateTime) -> Option<Timestamp> {
+        let datetime = match self {
+            Tz::Utc => return Some(Timestamp::from_datetime(Utc.from_utc_datetime(&naive))),
+            Tz::Local => Local.from_local_datetime(&naive).earliest()?.fixed_offset(),
+            Tz::Fixed(offset) => FixedOffset::east_opt(offset)?
+                .from_local_datetime(&naive)
+                .earliest()?,
+        };
+        Some(Timestamp::from_datetime(datetime))
+    }
+}
+
+/// Parses a date typed by a person: with `format` (a chrono format) if it matches, otherwise
+/// as RFC 3339, RFC 2822 or one of a few common layouts. Dates without a time zone are read
+/// in `tz`.
+pub fn parse_date(s: &str, format: &str, tz: Tz) -> Option<Timestamp> {
+    let s = s.trim();
+    let formats = std::iter::once(format).chain(COMMON_FORMATS.iter().copied());
+    for format in formats.clone() {
+        if let Ok(datetime) = DateTime::parse_from_str(s, format) {
+            return Some(Timestamp::from_datetime(datetime));
+        }
+    }
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(s).or(DateTime::parse_from_rfc2822(s)) {
+        return Some(Timestamp::from_datetime(datetime));
+    }
+    formats.into_iter().find_map(|format| {
+        let naive = NaiveDateTime::parse_from_str(s, format).ok().or_else(|| {
+            NaiveDate::parse_from_str(s, format)
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })?;
+        tz.timestamp_from_local(naive)
+    })
+}
+
+/// The largest `google.protobuf.Duration`, about 10,000 years.
+const PROTO_DURATION_MAX_SECS: i64 = 315_576_000_000;
+
+/// Parses a `google.protobuf.Duration` in its JSON form, e.g. "3.5s" or "-0.000001s". Durations
+/// beyond the protobuf range of ±315,576,000,000s aren't.
+pub fn parse_proto_duration(s: &str) -> Option<Duration> {
+    let s = s.strip_suffix('s')?;
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s),
+    };
+    let (secs, frac) = s.split_once('.').unwrap_or((s, ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if secs.is_empty() || !is_digits(secs) || !is_digits(frac) || frac.len() > 9 {
+        return None;
+    }
+    if s.ends_with('.') {
+        return None;
+    }
+    let secs: i64 = secs.parse().ok()?;
+    if secs > PROTO_DURATION_MAX_SECS {
+        return None;
+    }
+    let nanos: i64 = format!("{:0<9}", frac).parse().ok()?;
+    let duration = Duration::seconds(secs).checked_add(&Duration::nanoseconds(nanos))?;
+    Some(if negative { -duration } else { duration })
+}
+
+/// How durations, and times relative to an anchor, are written.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DurationStyle {
+    /// `556:00:00.5`: hours, minutes and seconds, with a fraction if there is one.
+    #[default]
+    Clock,
+    /// `3w 2d 4h`
+    Compact,
+    /// `P3W2DT4H`
+    Iso8601,
+    /// `3 weeks`, or `3 weeks ago` when relative. Only the largest unit is kept.
+    Verbose,
+    /// `2001600.500s`: seconds, always with millisecond precision.
+    Millis,
+}
+
+pub const DURATION_STYLES: &[DurationStyle] = &[
+    DurationStyle::Clock,
+    DurationStyle::Compact,
+    DurationStyle::Iso8601,
+    DurationStyle::Verbose,
+    DurationStyle::Millis,
+];
+
+/// Whole-second units, largest first: seconds in one, compact and verbose names.
+const UNITS: [(i64, &str, &str); 5] = [
+    (604_800, "w", "week"),
+    (86_400, "d", "day"),
+    (3_600, "h", "hour"),
+    (60, "m", "minute"),
+    (1, "s", "second"),
+];
+
+/// Sub-second units, largest first: nanoseconds in one, compact and verbose names.
+const SUBSEC_UNITS: [(i64, &str, &str); 3] = [
+    (1_000_000, "ms", "millisecond"),
+    (1_000, "µs", "microsecond"),
+    (1, "ns", "nanosecond"),
+];
+
+impl DurationStyle {
+    pub fn name(self) -> &'static str {
+        match self {
+            DurationStyle::Clock => "clock",
+            DurationStyle::Compact => "compact",
+            DurationStyle::Iso8601 => "iso8601",
+            DurationStyle::Verbose => "verbose",
+            DurationStyle::Millis => "millis",
+        }
+    }
+
+    /// An example of the style, for menus.
+    pub fn example(self) -> &'static str {
+        match self {
+            DurationStyle::Clock => "556:00:00",
+            DurationStyle::Compact => "3w 2d 4h",
+            DurationStyle::Iso8601 => "P3W2DT4H",
+            DurationStyle::Verbose => "3 weeks ago",
+            DurationStyle::Millis => "2001600.000s",
+        }
+    }
+
+    pub fn format(self, duration: Duration) -> String {
+        let sign = if duration < Duration::zero() { "-" } else { "" };
+        let abs = if duration < Duration::zero() {
+            -duration
+        } else {
+            duration
+        };
+        let secs = abs.num_seconds();
+        let nanos = (abs - Duration::seconds(secs))
+            .num_nanoseconds()
+            .unwrap_or(0);
+        // Each unit's count, largest first, with the seconds' fraction left in `nanos`.
+        let mut rest = secs;
+        let counts = UNITS.map(|(unit, _, _)| {
+            let count = rest / unit;
+            rest %= unit;
+            count
+        });
+        let mut rest = nanos;
+        let subsec_counts = SUBSEC_UNITS.map(|(unit, _, _)| {
+            let count = rest / unit;
+            rest %= unit;
+            count
+        });
+        let fraction = match nanos {
+            0 => String::new(),
+            nanos => format!(".{}", format!("{:09}", nanos).trim_end_matches('0')),
+        };
+        let body = match self {
+            DurationStyle::Clock => format!(
+                "{:02}:{:02}:{:02}{}",
+                secs / 3600,
+                (secs % 3600) / 60,
+                secs % 60,
+                fraction
+            ),
+            DurationStyle::Compact => {
+                let parts: Vec<String> = counts
+                    .iter()
+                    .zip(UNITS)
+                    .chain(subsec_counts.iter().zip(SUBSEC_UNITS))
+                    .filter(|(count, _)| **count != 0)
+                    .map(|(count, (_, name, _))| format!("{}{}", count, name))
+                    .collect();
+                match parts.is_empty() {
+                    true => "0s".to_owned(),
+                    false => parts.join(" "),
+                }
+            }
+            DurationStyle::Iso8601 => {
+                let [weeks, days, hours, minutes, seconds] = counts;
+                let mut out = "P".to_owned();
+                for (count, designator) in [(weeks, 'W'), (days, 'D')] {
+                    if count != 0 {
+                        out += &format!("{}{}", count, designator);
+                    }
+                }
+                if hours != 0 || minutes != 0 || seconds != 0 || nanos != 0 || out == "P" {
+                    out.push('T');
+                    for (count, designator) in [(hours, 'H'), (minutes, 'M')] {
+                        if count != 0 {
+                            out += &format!("{}{}", count, designator);
+                        }
+                    }
+                    if seconds != 0 || nanos != 0 || out == "PT" {
+                        out += &format!("{}{}S", seconds, fraction);
+                    }
+                }
+                out
+            }
+            DurationStyle::Verbose => {
+                let (count, name) = counts
+                    .iter()
+                    .zip(UNITS)
+                    .chain(subsec_counts.iter().zip(SUBSEC_UNITS))
+                    .find(|(count, _)| **count != 0)
+                    .map_or((0, "second"), |(count, (_, _, name))| (*count, name));
+                let plural = if count == 1 { "" } else { "s" };
+                format!("{} {}{}", count, name, plural)
+            }
+            DurationStyle::Millis => format!("{}.{:03}s", secs, nanos / 1_000_000),
+        };
+        format!("{}{}", sign, body)
+    }
+
+    /// A signed difference from an anchor: `+00:00:05`, or `5 seconds ago` / `in 5 seconds`.
+    pub fn format_relative(self, diff: Duration) -> String {
+        match self {
+            DurationStyle::Verbose if diff.is_zero() => "now".to_owned(),
+            DurationStyle::Verbose if diff < Duration::zero() => {
+                format!("{} ago", self.format(-diff))
+            }
+            DurationStyle::Verbose => format!("in {}", self.format(diff)),
+            _ if diff < Duration::zero() => self.format(diff),
+            _ => format!("+{}", self.format(diff)),
+        }
+    }
+}
+
+impl Display for DurationStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for DurationStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DURATION_STYLES
+            .iter()
+            .find(|style| style.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = DURATION_STYLES.iter().map(|style| style.name()).collect();
+                format!(
+                    "unknown duration style {:?}, expected one of {:?}",
+                    s, names
+                )
+            })
+    }
+}
+
+/// Parses a duration typed by a person: compact like "-1h 30m" or "2w", or protobuf like "3.5s".
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    if let Some(duration) = parse_proto_duration(s) {
+        return Some(duration);
+    }
+    let (negative, mut rest) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    if rest.trim().is_empty() {
+        return None;
+    }
+    let mut duration = Duration::zero();
+    while !rest.trim_start().is_empty() {
+        rest = rest.trim_start();
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        let count: i64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let name_len = rest.bytes().take_while(u8::is_ascii_alphabetic).count();
+        let (name, tail) = rest.split_at(name_len);
+        let part = match UNITS.iter().find(|(_, unit_name, _)| *unit_name == name) {
+            // `Duration::seconds` panics past its range, milliseconds can't.
+            Some((unit, _, _)) => {
+                Duration::milliseconds(count.checked_mul(*unit)?.checked_mul(1000)?)
+            }
+            None => match SUBSEC_UNITS
+                .iter()
+                .find(|(_, unit_name, _)| *unit_name == name)
+            {
+                Some((unit, _, _)) => Duration::nanoseconds(count.checked_mul(*unit)?),
+                None => return None,
+            },
+        };
+        duration = duration.checked_add(&part)?;
+        rest = tail;
+    }
+    Some(if negative { -duration } else { duration })
+}
+
+pub fn duration_to_str(duration: Duration) -> String {
+    DurationStyle::Clock.format(duration)
 }
 
 #[cfg(test)]
@@ -24,4 +703,200 @@ mod tests {
     fn test() {
         assert_eq!(year_to_ts(1970), Some(0))
     }
+
+    #[test]
+    fn test_proto_duration() {
+        assert_eq!(parse_proto_duration("3s"), Some(Duration::seconds(3)));
+        assert_eq!(
+            parse_proto_duration("3.5s"),
+            Some(Duration::milliseconds(3500))
+        );
+        assert_eq!(
+            parse_proto_duration("-0.000000001s"),
+            Some(Duration::nanoseconds(-1))
+        );
+        assert_eq!(
+            parse_proto_duration("-315576000000s"),
+            Some(Duration::seconds(-315_576_000_000))
+        );
+        for s in [
+            "",
+            "s",
+            "3",
+            "3.s",
+            ".5s",
+            "1.0000000001s",
+            "3.5 s",
+            "+3s",
+            "315576000001s",
+            "10000000000000000s",
+            "99999999999999999999s",
+        ] {
+            assert_eq!(parse_proto_duration(s), None, "{}", s);
+        }
+        assert_eq!(
+            duration_to_str(Duration::milliseconds(-3723500)),
+            "-01:02:03.5"
+        );
+        assert_eq!(Timestamp::new(-1, 1).unwrap().to_string(), "-0.999999999");
+    }
+
+    #[test]
+    fn test_duration_styles() {
+        let duration = Duration::weeks(3) + Duration::days(2) + Duration::hours(4);
+        let expected = [
+            (DurationStyle::Clock, "556:00:00"),
+            (DurationStyle::Compact, "3w 2d 4h"),
+            (DurationStyle::Iso8601, "P3W2DT4H"),
+            (DurationStyle::Verbose, "3 weeks"),
+            (DurationStyle::Millis, "2001600.000s"),
+        ];
+        for (style, expected) in expected {
+            assert_eq!(style.format(duration), expected);
+            assert_eq!(style.name().parse(), Ok(style));
+        }
+        let short = Duration::milliseconds(-1500);
+        assert_eq!(DurationStyle::Compact.format(short), "-1s 500ms");
+        assert_eq!(DurationStyle::Iso8601.format(short), "-PT1.5S");
+        assert_eq!(DurationStyle::Millis.format(short), "-1.500s");
+        assert_eq!(DurationStyle::Iso8601.format(Duration::zero()), "PT0S");
+        assert_eq!(
+            DurationStyle::Verbose.format_relative(short),
+            "1 second ago"
+        );
+        assert_eq!(
+            DurationStyle::Verbose.format_relative(Duration::minutes(1)),
+            "in 1 minute"
+        );
+        assert_eq!(
+            DurationStyle::Clock.format_relative(Duration::seconds(5)),
+            "+00:00:05"
+        );
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let expected = Timestamp::from_secs(1692694500);
+        let fmt = "%d.%m.%Y %H:%M";
+        for s in [
+            "22.08.2023 08:55",
+            "2023-08-22 08:55:00",
+            "2023-08-22T08:55",
+            "22 Aug 2023 08:55:00",
+        ] {
+            assert_eq!(parse_date(s, fmt, Tz::Utc), Some(expected), "{}", s);
+        }
+        assert_eq!(
+            parse_date("2023-08-22T10:55:00+02:00", fmt, Tz::Local),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_date("Tue, 22 Aug 2023 08:55:00 GMT", fmt, Tz::Local),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_date("2023-08-22 14:25", fmt, Tz::Fixed(19800)),
+            Some(expected)
+        );
+        assert_eq!(parse_date("yesterday", fmt, Tz::Utc), None);
+
+        let later = expected.checked_add(Duration::milliseconds(-1500)).unwrap();
+        assert_eq!(later, Timestamp::new(1692694498, 500_000_000).unwrap());
+        assert_eq!(later.since(expected), Some(Duration::milliseconds(-1500)));
+        let far = Timestamp::from_secs(1 << 62);
+        assert_eq!(far.since(Timestamp::from_secs(0)), None);
+        assert_eq!(far.since(Timestamp::from_secs(-far.secs)), None);
+        assert_eq!(
+            format_like(later, "2023-08-22T10:55:00+02:00", "rfc3339").unwrap(),
+            "2023-08-22T10:54:58+02:00"
+        );
+        assert_eq!(
+            format_like(later, "2023-08-22T08:55:00.000000Z", "rfc3339").unwrap(),
+            "2023-08-22T08:54:58.500000Z"
+        );
+        assert_eq!(format_like(later, "x", fmt).unwrap(), "22.08.2023 08:54");
+        assert_eq!(parse_duration("-1h 30m"), Some(-Duration::minutes(90)));
+        assert_eq!(parse_duration("2w1d"), Some(Duration::days(15)));
+        assert_eq!(parse_duration("3.5s"), Some(Duration::milliseconds(3500)));
+        assert_eq!(parse_duration("+250ms"), Some(Duration::milliseconds(250)));
+        for s in [
+            "",
+            "-",
+            "1",
+            "h",
+            "1y",
+            "1h -5m",
+            "9999999999999999s",
+            "99999999999999d",
+        ] {
+            assert_eq!(parse_duration(s), None, "{}", s);
+        }
+
+        let family = |name| EpochFamily::by_name(name).unwrap();
+        let ts = Timestamp::new(1692694500, 250_000_000).unwrap();
+        assert_eq!(
+            family("Unix milliseconds").value_of(ts).unwrap(),
+            "1692694500250"
+        );
+        assert_eq!(
+            family("Unix seconds").value_of(ts).unwrap(),
+            "1692694500.25"
+        );
+        let excel = family("Excel serial (1900)").value_of(expected).unwrap();
+        let back = family("Excel serial (1900)").timestamp_from_float(excel.parse().unwrap());
+        assert_eq!(back.map(|ts| ts.secs), Some(expected.secs));
+    }
+
+    #[test]
+    fn test_tz() {
+        for s in ["UTC", "Local", "+05:30", "-08:00"] {
+            assert_eq!(s.parse::<Tz>().unwrap().to_string(), s);
+        }
+        assert_eq!("-08".parse(), Ok(Tz::Fixed(-8 * 3600)));
+        for s in ["", "5", "+25:00", "+05:60", "utc"] {
+            assert!(s.parse::<Tz>().is_err(), "{}", s);
+        }
+        assert_eq!(
+            ts_to_str(1692694500, "%Y-%m-%d %H:%M %z", Tz::Fixed(19800)),
+            Some("2023-08-22 14:25 +0530".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_epoch_families() {
+        let family = |name| EpochFamily::by_name(name).unwrap();
+        let expected = Timestamp::from_secs(1692694500); // 2023-08-22 08:55:00
+        assert_eq!(
+            family("Unix seconds").timestamp_from_int(1692694500),
+            Some(expected)
+        );
+        assert_eq!(
+            family("Unix milliseconds").timestamp_from_int(1692694500123),
+            Timestamp::new(1692694500, 123_000_000)
+        );
+        assert_eq!(
+            family("Windows FILETIME / LDAP").timestamp_from_int(133371681000000000),
+            Some(expected)
+        );
+        assert_eq!(
+            family(".NET ticks").timestamp_from_int(638282913000000000),
+            Some(expected)
+        );
+        assert_eq!(
+            family("Apple/Cocoa seconds").timestamp_from_int(714387300),
+            Some(expected)
+        );
+        assert_eq!(
+            family("Excel serial (1900)").timestamp_from_float(45160.375),
+            Some(Timestamp::from_secs(1692694800))
+        );
+        assert_eq!(
+            family("Excel serial (1904)").timestamp_from_float(43698.375),
+            Some(Timestamp::from_secs(1692694800))
+        );
+        assert_eq!(
+            family("GPS seconds").timestamp_from_int(1376729718),
+            Some(expected)
+        );
+    }
 }
@@ -0,0 +1,386 @@
+use std::path::PathBuf;
+
+use chrono::Duration;
+
+use crate::datetime::Timestamp;
+use crate::files::OpenedFile;
+use crate::json_crawl::{Hit, JsonPath, JsonPathPart};
+use crate::jwt::{parse_input, ParsedInput};
+use crate::rewrite;
+use crate::scanner::Scanner;
+use crate::spans::locate_hits;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::watch::{FileWatcher, SeenHits};
+
+/// A JSON document open in a tab.
+#[derive(Clone, Debug)]
+pub struct Document {
+    /// Unique among the open documents, so it can prefix paths in the combined view.
+    pub name: String,
+    /// The file it was opened from, if known.
+    pub path: Option<PathBuf>,
+    pub json_body: String,
+    /// Set while the file is watched for changes.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub watch: Option<Watch>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug)]
+pub struct Watch {
+    pub watcher: FileWatcher,
+    pub seen: SeenHits,
+    /// A hash of the text as last loaded, to tell whether it was edited since.
+    loaded: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn text_hash(text: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl Document {
+    pub fn new(name: String, path: Option<PathBuf>, json_body: String) -> Self {
+        Document {
+            name,
+            path,
+            json_body,
+            #[cfg(not(target_arch = "wasm32"))]
+            watch: None,
+        }
+    }
+    /// The document's JSON, with any JWTs decoded (see `parse_input`).
+    pub fn parse(&self) -> Result<ParsedInput, String> {
+        parse_input(&self.json_body)
+    }
+    /// The hits in the document's parsed JSON, knowing where they are in its text, or that
+    /// they're in a JWT.
+    pub fn scan(&self, input: &ParsedInput, scanner: &Scanner) -> Vec<Hit> {
+        let mut hits = scanner.scan(&input.value);
+        input.mark_jwt_hits(&mut hits);
+        locate_hits(&self.json_body, &mut hits);
+        hits
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tab {
+    Document(usize),
+    /// All the documents' hits in one timeline.
+    Combined,
+}
+
+/// The open documents, and which tab is shown. There's always at least one document.
+pub struct Documents {
+    documents: Vec<Document>,
+    tab: Tab,
+}
+
+impl Default for Documents {
+    fn default() -> Self {
+        Self::new(Document::new("Untitled".to_owned(), None, String::new()))
+    }
+}
+
+impl Documents {
+    pub fn new(document: Document) -> Self {
+        Documents {
+            documents: vec![document],
+            tab: Tab::Document(0),
+        }
+    }
+
+    pub fn documents(&self) -> &[Document] {
+        &self.documents
+    }
+
+    pub fn tab(&self) -> Tab {
+        self.tab
+    }
+
+    pub fn select(&mut self, tab: Tab) {
+        self.tab = match tab {
+            Tab::Document(i) if i >= self.documents.len() => Tab::Document(0),
+            Tab::Combined if self.documents.len() < 2 => Tab::Document(0),
+            tab => tab,
+        };
+    }
+
+    /// The document shown, or `None` in the combined view.
+    pub fn active(&self) -> Option<&Document> {
+        match self.tab {
+            Tab::Document(i) => self.documents.get(i),
+            Tab::Combined => None,
+        }
+    }
+
+    pub fn active_mut(&mut self) -> Option<&mut Document> {
+        match self.tab {
+            Tab::Document(i) => self.documents.get_mut(i),
+            Tab::Combined => None,
+        }
+    }
+
+    /// The index of the document a path shown in the current tab is in, and the path inside it:
+    /// in the combined tab, paths start with their document's name.
+    pub fn locate(&self, path: &JsonPath) -> Option<(usize, JsonPath)> {
+        match self.tab {
+            Tab::Document(i) => Some((i, path.clone())),
+            Tab::Combined => {
+                let (JsonPathPart::Field(name), rest) = path.0.split_first()? else {
+                    return None;
+                };
+                let i = self.documents.iter().position(|doc| &doc.name == name)?;
+                Some((i, JsonPath(rest.to_vec())))
+            }
+        }
+    }
+
+    /// Rewrites a hit shown in the current tab as `ts`, in its document's text (see `edit_hit`).
+    pub fn edit_hit(&mut self, hit: &Hit, ts: Timestamp) -> Result<(), String> {
+        let (i, path) = self
+            .locate(&hit.path)
+            .ok_or(format!("{} isn't in any document", hit.path))?;
+        let hit = Hit {
+            path,
+            ..hit.clone()
+        };
+        let document = &mut self.documents[i];
+        document.json_body = rewrite::edit_hit(&document.json_body, &hit, ts)?;
+        Ok(())
+    }
+
+    /// `name`, or `name (2)`, `name (3)`... if it's taken.
+    fn unique_name(&self, name: &str) -> String {
+        let taken = |candidate: &str| self.documents.iter().any(|doc| doc.name == candidate);
+        (1..)
+            .map(|n| match n {
+                1 => name.to_owned(),
+                n => format!("{} ({})", name, n),
+            })
+            .find(|candidate| !taken(candidate))
+            .expect("some name is free")
+    }
+
+    /// Opens the file in a new tab and shows it. An empty, untouched tab is reused.
+    pub fn open(&mut self, file: OpenedFile) {
+        if let [only] = self.documents.as_slice() {
+            if only.path.is_none() && only.json_body.trim().is_empty() {
+                self.documents.clear();
+            }
+        }
+        let name = self.unique_name(&file.name);
+        self.documents
+            .push(Document::new(name, file.path, file.contents));
+        self.tab = Tab::Document(self.documents.len() - 1);
+    }
+
+    /// Shows the contents in the tab named `name` that isn't backed by a file, opening one if needed.
+    pub fn replace_or_open(&mut self, name: &str, contents: String) {
+        let existing = self
+            .documents
+            .iter()
+            .position(|document| document.name == name && document.path.is_none());
+        match existing {
+            Some(i) => {
+                self.documents[i].json_body = contents;
+                self.tab = Tab::Document(i);
+            }
+            None => self.open(OpenedFile {
+                name: name.to_owned(),
+                path: None,
+                contents,
+            }),
+        }
+    }
+
+    pub fn close(&mut self, index: usize) {
+        self.documents.remove(index);
+        if self.documents.is_empty() {
+            *self = Self::default();
+        }
+        self.select(match self.tab {
+            Tab::Document(i) if i > index => Tab::Document(i - 1),
+            Tab::Document(i) if i == index => Tab::Document(index.saturating_sub(1)),
+            tab => tab,
+        });
+    }
+
+    /// Starts or stops watching the shown document's file for changes.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_watched(&mut self, watched: bool, scanner: &Scanner) {
+        let Some(document) = self.active_mut() else {
+            return;
+        };
+        document.watch = match (watched, &document.path) {
+            (true, Some(path)) => {
+                let mut seen = SeenHits::default();
+                if let Ok(input) = document.parse() {
+                    seen.update(&scanner.scan(&input.value));
+                }
+                Some(Watch {
+                    watcher: FileWatcher::new(path.clone()),
+                    seen,
+                    loaded: text_hash(&document.json_body),
+                })
+            }
+            _ => None,
+        };
+    }
+
+    /// Whether the hit appeared in the last reload of the shown document.
+    pub fn is_new(&self, hit: &Hit) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(watch) = self.active().and_then(|document| document.watch.as_ref()) {
+            return watch.seen.is_new(hit);
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = hit;
+        false
+    }
+
+    /// Reloads the watched documents whose files changed. Returns the reloads' outcomes.
+    /// Documents edited since they were last loaded are left alone, with an error saying so.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn poll_watched(&mut self, scanner: &Scanner) -> Vec<Result<String, String>> {
+        let mut reloads = vec![];
+        for document in &mut self.documents {
+            let Some(watch) = &mut document.watch else {
+                continue;
+            };
+            let Some(contents) = watch.watcher.poll() else {
+                continue;
+            };
+            if text_hash(&document.json_body) != watch.loaded {
+                reloads.push(Err(format!(
+                    "{} changed on disk, but it has unsaved edits: kept them",
+                    document.name
+                )));
+                continue;
+            }
+            reloads.push(contents.map(|contents| {
+                let parsed = parse_input(&contents);
+                watch.loaded = text_hash(&contents);
+                document.json_body = contents;
+                match parsed {
+                    Ok(input) => {
+                        watch.seen.update(&scanner.scan(&input.value));
+                        format!(
+                            "Reloaded {} ({} new timestamps)",
+                            document.name,
+                            watch.seen.new_count()
+                        )
+                    }
+                    Err(err) => format!("Reloaded {} ({})", document.name, err),
+                }
+            }));
+        }
+        reloads
+    }
+
+    /// What `scan` finds in every document, with the document's name as the first path
+    /// component, and the documents that failed to parse.
+    fn combined<T>(
+        &self,
+        scan: impl Fn(&Document, &ParsedInput) -> Vec<T>,
+        path: impl Fn(&mut T) -> &mut JsonPath,
+    ) -> (Vec<T>, Vec<String>) {
+        let mut found = vec![];
+        let mut errors = vec![];
+        for document in &self.documents {
+            match document.parse() {
+                Ok(input) => found.extend(scan(document, &input).into_iter().map(|mut item| {
+                    path(&mut item)
+                        .0
+                        .insert(0, JsonPathPart::Field(document.name.clone()));
+                    item
+                })),
+                Err(err) => errors.push(format!("{}: {}", document.name, err)),
+            }
+        }
+        (found, errors)
+    }
+
+    pub fn combined_hits(&self, scanner: &Scanner) -> (Vec<Hit>, Vec<String>) {
+        self.combined(
+            |document, input| document.scan(input, scanner),
+            |hit| &mut hit.path,
+        )
+    }
+
+    pub fn combined_durations(&self, scanner: &Scanner) -> Vec<(JsonPath, Duration)> {
+        self.combined(|_, input| scanner.durations(&input.value), |(path, _)| path)
+            .0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, contents: &str) -> OpenedFile {
+        OpenedFile {
+            name: name.to_owned(),
+            path: None,
+            contents: contents.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_documents() {
+        let mut documents = Documents::default();
+        documents.open(file("a.json", r#"{"t": 1692694500}"#));
+        documents.open(file("a.json", r#"[1692694501]"#));
+        documents.open(file("b.json", "not json"));
+        let names: Vec<_> = documents.documents().iter().map(|doc| &doc.name).collect();
+        assert_eq!(names, ["a.json", "a.json (2)", "b.json"]);
+        assert_eq!(documents.tab(), Tab::Document(2));
+
+        let (hits, errors) = documents.combined_hits(&Scanner::new().years(2020, 2030));
+        let paths: Vec<_> = hits.iter().map(|hit| hit.path.to_string()).collect();
+        assert_eq!(paths, ["a.json.t", "a.json (2)[0]"]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            hits[1].span.as_ref().map(|span| span.range.clone()),
+            Some(1..11)
+        );
+
+        documents.select(Tab::Combined);
+        documents
+            .edit_hit(&hits[1], Timestamp::from_secs(1700000000))
+            .unwrap();
+        assert_eq!(documents.documents()[1].json_body, "[1700000000]");
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let path = std::env::temp_dir().join(format!(
+                "json_unix_time_documents_{}.json",
+                std::process::id()
+            ));
+            std::fs::write(&path, "[1692694500]").unwrap();
+            let scanner = Scanner::new().years(2020, 2030);
+            documents.open(OpenedFile {
+                path: Some(path.clone()),
+                ..file("c.json", "[1692694500]")
+            });
+            documents.set_watched(true, &scanner);
+            std::fs::write(&path, "[1692694500, 1692694600]").unwrap();
+            assert!(documents.poll_watched(&scanner)[0].is_ok());
+            documents.active_mut().unwrap().json_body = "[1]".to_owned();
+            std::fs::write(&path, "[1692694500, 1692694600, 1692694700]").unwrap();
+            assert!(documents.poll_watched(&scanner)[0].is_err());
+            assert_eq!(documents.active().unwrap().json_body, "[1]");
+            std::fs::remove_file(&path).unwrap();
+            documents.close(3);
+        }
+
+        documents.close(2);
+        documents.close(0);
+        assert_eq!(documents.tab(), Tab::Document(0));
+        assert_eq!(documents.active().unwrap().name, "a.json (2)");
+    }
+}
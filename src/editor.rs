@@ -0,0 +1,185 @@
+use std::ops::Range;
+
+use egui::text::{LayoutJob, TextFormat};
+use egui::util::cache::{ComputerMut, FrameCache};
+use egui::{Color32, FontId};
+
+use crate::json_text::{tokenize, TokenKind};
+
+/// The editor's font and colors, taken from the egui style.
+#[derive(Clone, PartialEq, Hash, Debug)]
+pub struct Theme {
+    font: FontId,
+    dark: bool,
+    text: Color32,
+    timestamp: Color32,
+    underline: Color32,
+}
+
+impl Theme {
+    pub fn from_style(style: &egui::Style) -> Self {
+        Theme {
+            font: egui::TextStyle::Monospace.resolve(style),
+            dark: style.visuals.dark_mode,
+            text: style.visuals.widgets.inactive.text_color(),
+            timestamp: style.visuals.selection.bg_fill.linear_multiply(0.4),
+            underline: style.visuals.selection.stroke.color,
+        }
+    }
+
+    fn color(&self, kind: Option<TokenKind>) -> Color32 {
+        let (dark, light) = match kind {
+            Some(TokenKind::Key) => (
+                Color32::from_rgb(156, 220, 254),
+                Color32::from_rgb(4, 81, 165),
+            ),
+            Some(TokenKind::String) => (
+                Color32::from_rgb(206, 145, 120),
+                Color32::from_rgb(163, 21, 21),
+            ),
+            Some(TokenKind::Number) => (
+                Color32::from_rgb(181, 206, 168),
+                Color32::from_rgb(9, 134, 88),
+            ),
+            Some(TokenKind::Literal) => (
+                Color32::from_rgb(86, 156, 214),
+                Color32::from_rgb(0, 0, 255),
+            ),
+            Some(TokenKind::Invalid) => (
+                Color32::from_rgb(244, 71, 71),
+                Color32::from_rgb(205, 49, 49),
+            ),
+            Some(TokenKind::Punctuation) | None => return self.text,
+        };
+        match self.dark {
+            true => dark,
+            false => light,
+        }
+    }
+}
+
+/// Lays out the JSON editor's text, colored by syntax, with the timestamps at `highlighted`
+/// (byte ranges, in any order) highlighted. Ranges that don't fit the text, e.g. from before an
+/// edit, are skipped.
+pub fn layout_job(text: &str, highlighted: &[Range<usize>], theme: &Theme) -> LayoutJob {
+    let mut ranges: Vec<_> = highlighted
+        .iter()
+        .filter(|range| text.get((*range).clone()).is_some())
+        .cloned()
+        .collect();
+    ranges.sort_by_key(|range| range.start);
+    let mut timestamps: Vec<Range<usize>> = vec![];
+    for range in ranges {
+        // Nested ones, e.g. a protobuf Timestamp's `seconds`, are inside one already.
+        if timestamps
+            .last()
+            .map_or(true, |last| range.start >= last.end)
+        {
+            timestamps.push(range);
+        }
+    }
+    let tokens = tokenize(text);
+    // Sections start wherever a token or a timestamp starts or ends.
+    let mut breaks: Vec<usize> = tokens
+        .iter()
+        .flat_map(|(_, range)| [range.start, range.end])
+        .chain(timestamps.iter().flat_map(|range| [range.start, range.end]))
+        .chain([0, text.len()])
+        .collect();
+    breaks.sort_unstable();
+    breaks.dedup();
+    let (mut tokens, mut timestamps) = (tokens.iter().peekable(), timestamps.iter().peekable());
+    let mut job = LayoutJob::default();
+    for pair in breaks.windows(2) {
+        let (start, end) = (pair[0], pair[1]);
+        while tokens.next_if(|(_, range)| range.end <= start).is_some() {}
+        while timestamps.next_if(|range| range.end <= start).is_some() {}
+        let kind = tokens
+            .peek()
+            .filter(|(_, range)| range.start <= start)
+            .map(|(kind, _)| *kind);
+        let mut format = TextFormat::simple(theme.font.clone(), theme.color(kind));
+        if timestamps
+            .peek()
+            .map_or(false, |range| range.start <= start)
+        {
+            format.background = theme.timestamp;
+            format.underline = egui::Stroke::new(1.0, theme.underline);
+        }
+        job.append(&text[start..end], 0.0, format);
+    }
+    job
+}
+
+#[derive(Default)]
+struct Highlighter;
+
+impl ComputerMut<(&str, &[Range<usize>], &Theme), LayoutJob> for Highlighter {
+    fn compute(
+        &mut self,
+        (text, highlighted, theme): (&str, &[Range<usize>], &Theme),
+    ) -> LayoutJob {
+        layout_job(text, highlighted, theme)
+    }
+}
+
+/// `layout_job`, cached from one frame to the next, so large documents aren't tokenized again
+/// until they change.
+pub fn cached_layout_job(
+    ctx: &egui::Context,
+    text: &str,
+    highlighted: &[Range<usize>],
+    theme: &Theme,
+) -> LayoutJob {
+    ctx.memory_mut(|memory| {
+        memory
+            .caches
+            .cache::<FrameCache<LayoutJob, Highlighter>>()
+            .get((text, highlighted, theme))
+    })
+}
+
+/// The byte offset of a character index in `text`.
+pub fn byte_offset(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map_or(text.len(), |(offset, _)| offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_job() {
+        let text = r#"{"é": 1692694500, "p": {"seconds": 1692694500}}"#;
+        let theme = Theme::from_style(&egui::Style::default());
+        let job = layout_job(text, &[24..47, 7..17, 36..46, 40..100], &theme);
+        assert_eq!(job.text, text);
+        let highlighted: Vec<_> = job
+            .sections
+            .iter()
+            .filter(|section| section.format.background == theme.timestamp)
+            .map(|section| &text[section.byte_range.clone()])
+            .collect();
+        assert_eq!(
+            highlighted,
+            [
+                "1692694500",
+                "{",
+                r#""seconds""#,
+                ":",
+                " ",
+                "1692694500",
+                "}"
+            ]
+        );
+        let number = job
+            .sections
+            .iter()
+            .find(|section| &text[section.byte_range.clone()] == "1692694500")
+            .unwrap();
+        assert_eq!(number.format.color, theme.color(Some(TokenKind::Number)));
+        assert_eq!(byte_offset(text, 3), 4);
+    }
+}
@@ -0,0 +1,41 @@
+use crate::datetime::Timestamp;
+
+/// Which hits to keep, by how they compare to the current time (e.g. a token's `exp`).
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub enum ExpiryFilter {
+    #[default]
+    All,
+    /// Only the ones in the past.
+    Expired,
+    /// Only the ones in the past, or less than this many seconds ahead.
+    ExpiringWithin(i64),
+}
+
+impl ExpiryFilter {
+    pub fn keeps(self, ts: Timestamp, now: Timestamp) -> bool {
+        match self {
+            ExpiryFilter::All => true,
+            ExpiryFilter::Expired => ts <= now,
+            ExpiryFilter::ExpiringWithin(secs) => ts.secs <= now.secs.saturating_add(secs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiry_filter() {
+        let now = Timestamp::from_secs(1692694500);
+        let past = Timestamp::new(1692694499, 500_000_000).unwrap();
+        let soon = Timestamp::from_secs(1692694500 + 60);
+        let later = Timestamp::from_secs(1692694500 + 3600);
+        assert!(ExpiryFilter::All.keeps(later, now));
+        assert!(ExpiryFilter::Expired.keeps(past, now));
+        assert!(!ExpiryFilter::Expired.keeps(soon, now));
+        let filter = ExpiryFilter::ExpiringWithin(300);
+        assert!(filter.keeps(past, now) && filter.keeps(soon, now));
+        assert!(!filter.keeps(later, now));
+    }
+}
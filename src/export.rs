@@ -0,0 +1,78 @@
+use crate::datetime::{timestamp_to_str, DurationStyle, Timestamp, Tz};
+use crate::gaps::deltas;
+use crate::json_crawl::Hit;
+
+/// Quotes a CSV field if it needs it (RFC 4180).
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// The hits as a CSV table, with the same columns as the app's table.
+pub fn hits_to_csv(
+    hits: &[Hit],
+    fmt: &str,
+    tz: Tz,
+    anchors: &[(String, Option<Timestamp>)],
+    duration_style: DurationStyle,
+) -> String {
+    let mut header = ["path", "value", "interpretation", "confidence", "time"]
+        .map(str::to_owned)
+        .to_vec();
+    header.extend(
+        anchors
+            .iter()
+            .map(|(name, _)| format!("relative to {}", name)),
+    );
+    header.push("delta_prev".to_owned());
+    let header: Vec<String> = header.iter().map(|field| csv_field(field)).collect();
+    let mut csv = header.join(",") + "\n";
+    for (hit, delta) in hits.iter().zip(deltas(hits)) {
+        let mut row = vec![
+            hit.path.to_string(),
+            hit.raw.clone(),
+            hit.interpretation.to_string(),
+            format!("{:.2}", hit.confidence),
+            timestamp_to_str(hit.ts, fmt, tz).unwrap_or_default(),
+        ];
+        row.extend(anchors.iter().map(|(_, anchor)| {
+            anchor
+                .and_then(|anchor| hit.ts.since(anchor))
+                .map(|diff| duration_style.format_relative(diff))
+                .unwrap_or_default()
+        }));
+        row.push(
+            delta
+                .map(|delta| duration_style.format(delta))
+                .unwrap_or_default(),
+        );
+        let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+
+    #[test]
+    fn test_hits_to_csv() {
+        let hits = Scanner::new()
+            .years(2020, 2030)
+            .scan_str(r#"{"created_at": 1692694500, "a,b": "2023-08-22T08:55:01Z"}"#)
+            .unwrap();
+        let anchors = [("start".to_owned(), Some(Timestamp::from_secs(1692694500)))];
+        assert_eq!(
+            hits_to_csv(&hits, "%H:%M:%S", Tz::Utc, &anchors, DurationStyle::Clock),
+            "path,value,interpretation,confidence,time,relative to start,delta_prev\n\
+            \"a,b\",\"\"\"2023-08-22T08:55:01Z\"\"\",Format rfc3339,0.90,08:55:01,+00:00:01,\n\
+            created_at,1692694500,Unix seconds,0.80,08:55:00,+00:00:00,-00:00:01\n"
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+/// A document loaded from disk (or, on the web, from the browser's file picker).
+#[derive(Clone, Debug)]
+pub struct OpenedFile {
+    pub name: String,
+    /// Only known natively.
+    pub path: Option<PathBuf>,
+    pub contents: String,
+}
+
+impl OpenedFile {
+    pub fn read(path: PathBuf) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|err| format!("can't read {}: {}", path.display(), err))?;
+        Ok(OpenedFile {
+            name: path.file_name().map_or(path.display().to_string(), |name| {
+                name.to_string_lossy().into_owned()
+            }),
+            path: Some(path),
+            contents,
+        })
+    }
+}
+
+/// Opens and saves files with the platform's dialogs. On the web the open dialog is async,
+/// so its result arrives through `poll_opened`.
+pub struct FileDialogs {
+    sender: Sender<Result<OpenedFile, String>>,
+    receiver: Receiver<Result<OpenedFile, String>>,
+}
+
+impl Default for FileDialogs {
+    fn default() -> Self {
+        let (sender, receiver) = channel();
+        Self { sender, receiver }
+    }
+}
+
+impl FileDialogs {
+    /// Asks the user for a JSON file to open.
+    pub fn open(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .add_filter("All files", &["*"])
+            .pick_file()
+        {
+            self.sender.send(OpenedFile::read(path)).ok();
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let sender = self.sender.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let Some(handle) = rfd::AsyncFileDialog::new()
+                    .add_filter("JSON", &["json"])
+                    .pick_file()
+                    .await
+                else {
+                    return;
+                };
+                let opened = String::from_utf8(handle.read().await)
+                    .map(|contents| OpenedFile {
+                        name: handle.file_name(),
+                        path: None,
+                        contents,
+                    })
+                    .map_err(|_| format!("{} is not valid UTF-8", handle.file_name()));
+                sender.send(opened).ok();
+            });
+        }
+    }
+
+    /// The file picked by the last `open`, once it's ready.
+    pub fn poll_opened(&self) -> Option<Result<OpenedFile, String>> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Asks the user where to save `contents` (on the web, downloads it).
+    /// Returns where it was saved, or `None` if the user canceled.
+    pub fn save(&self, file_name: &str, contents: &str) -> Result<Option<String>, String> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let Some(path) = rfd::FileDialog::new().set_file_name(file_name).save_file() else {
+                return Ok(None);
+            };
+            std::fs::write(&path, contents)
+                .map_err(|err| format!("can't write {}: {}", path.display(), err))?;
+            Ok(Some(path.display().to_string()))
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            download(file_name, contents).map_err(|err| format!("can't download: {:?}", err))?;
+            Ok(Some(file_name.to_owned()))
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn download(file_name: &str, contents: &str) -> Result<(), wasm_bindgen::JsValue> {
+    use wasm_bindgen::JsCast as _;
+
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("no document")?;
+    let parts = js_sys::Array::of1(&contents.into());
+    let blob = web_sys::Blob::new_with_str_sequence(&parts)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+    let link: web_sys::HtmlAnchorElement = document.create_element("a")?.dyn_into()?;
+    link.set_href(&url);
+    link.set_download(file_name);
+    link.click();
+    web_sys::Url::revoke_object_url(&url)
+}
@@ -0,0 +1,103 @@
+use chrono::Duration;
+
+use crate::json_crawl::Hit;
+use crate::rules::PathPattern;
+
+/// The time from the previous hit to each hit, in the given order. The first has no previous,
+/// and a time too long for a `Duration` is left out too.
+pub fn deltas(hits: &[Hit]) -> Vec<Option<Duration>> {
+    let previous = std::iter::once(None).chain(hits.iter().map(Some));
+    hits.iter()
+        .zip(previous)
+        .map(|(hit, previous)| hit.ts.since(previous?.ts))
+        .collect()
+}
+
+/// A summary of the intervals between consecutive timestamps.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct IntervalStats {
+    pub count: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+}
+
+impl IntervalStats {
+    /// The stats of the intervals between the hits once sorted by time, or `None` if there are
+    /// fewer than two hits or an interval is too long for a `Duration`.
+    pub fn of<'a>(hits: impl IntoIterator<Item = &'a Hit>) -> Option<Self> {
+        let mut times: Vec<_> = hits.into_iter().map(|hit| hit.ts).collect();
+        times.sort();
+        let mut intervals: Vec<_> = times
+            .windows(2)
+            .map(|pair| pair[1].since(pair[0]))
+            .collect::<Option<_>>()?;
+        intervals.sort();
+        let (&min, &max) = (intervals.first()?, intervals.last()?);
+        // Nearest rank, so every statistic is an interval that actually occurred.
+        let percentile = |p: usize| intervals[((intervals.len() * p + 99) / 100).max(1) - 1];
+        Some(IntervalStats {
+            count: intervals.len(),
+            min,
+            median: percentile(50),
+            p95: percentile(95),
+            max,
+        })
+    }
+}
+
+/// The interval stats of all the hits, and of each group of paths that only differ in array
+/// indices (e.g. `events[*].time`), for the groups with at least two hits.
+pub fn interval_stats(hits: &[Hit]) -> (Option<IntervalStats>, Vec<(PathPattern, IntervalStats)>) {
+    let mut patterns: Vec<PathPattern> = vec![];
+    for hit in hits {
+        let pattern = PathPattern::any_index(&hit.path);
+        if !patterns.contains(&pattern) {
+            patterns.push(pattern);
+        }
+    }
+    let by_pattern = patterns
+        .into_iter()
+        .filter_map(|pattern| {
+            let stats = IntervalStats::of(hits.iter().filter(|hit| pattern.matches(&hit.path)))?;
+            Some((pattern, stats))
+        })
+        .collect();
+    (IntervalStats::of(hits), by_pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime::Timestamp;
+    use crate::scanner::Scanner;
+
+    #[test]
+    fn test_interval_stats() {
+        let hits = Scanner::new()
+            .years(2020, 2030)
+            .scan_str(r#"{"a": [1692694500, 1692694510, 1692694530], "b": 1692694400.5}"#)
+            .unwrap();
+        let deltas = deltas(&hits);
+        assert_eq!(deltas[0], None);
+        assert_eq!(deltas[1], Some(Duration::seconds(10)));
+
+        let (all, by_pattern) = interval_stats(&hits);
+        let all = all.unwrap();
+        assert_eq!(all.count, 3);
+        assert_eq!(all.min, Duration::seconds(10));
+        assert_eq!(all.median, Duration::seconds(20));
+        assert_eq!(all.max, Duration::milliseconds(99_500));
+        assert_eq!(by_pattern.len(), 1);
+        assert_eq!(by_pattern[0].0.to_string(), "a[*]");
+        assert_eq!(by_pattern[0].1.p95, Duration::seconds(20));
+
+        // Pinned rules skip the year range, so hits can be further apart than a `Duration`.
+        let mut far = hits[..2].to_vec();
+        far[0].ts = Timestamp::from_secs(i64::MIN);
+        far[1].ts = Timestamp::from_secs(i64::MAX);
+        assert_eq!(super::deltas(&far), [None, None]);
+        assert_eq!(IntervalStats::of(&far), None);
+    }
+}
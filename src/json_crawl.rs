@@ -1,14 +1,19 @@
 use std::fmt::Display;
 
+use chrono::Duration;
 use serde_json::Value;
 
-#[derive(Clone, PartialEq, Eq, Debug, PartialOrd, Ord)]
+use crate::datetime::{parse_formatted, parse_proto_duration, EpochFamily, Timestamp};
+use crate::rules::{find_rule, Rule, RuleAction};
+use crate::spans::SourceSpan;
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
 pub enum JsonPathPart {
     Field(String),
     Index(usize),
 }
 
-#[derive(PartialEq, Eq, Debug, PartialOrd, Ord)]
+#[derive(Clone, Default, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
 pub struct JsonPath(pub Vec<JsonPathPart>);
 
 impl JsonPath {
@@ -20,8 +25,28 @@ impl JsonPath {
         vec.push(part);
         JsonPath(vec)
     }
-    pub fn is_prefix_of(&self, other: &Self) -> bool {
-        self.0 == other.0[..self.0.len().min(other.0.len())]
+    pub fn parent(&self) -> Option<Self> {
+        let (_, parent) = self.0.split_last()?;
+        Some(JsonPath(parent.to_vec()))
+    }
+    /// The closest object key on the path, e.g. `created_at` for `events[3].created_at` and `times[2]`.
+    pub fn last_field(&self) -> Option<&str> {
+        self.0.iter().rev().find_map(|part| match part {
+            JsonPathPart::Field(field) => Some(field.as_str()),
+            JsonPathPart::Index(_) => None,
+        })
+    }
+    pub fn get<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        self.0.iter().try_fold(value, |value, part| match part {
+            JsonPathPart::Field(field) => value.get(field),
+            JsonPathPart::Index(index) => value.get(index),
+        })
+    }
+    pub fn get_mut<'a>(&self, value: &'a mut Value) -> Option<&'a mut Value> {
+        self.0.iter().try_fold(value, |value, part| match part {
+            JsonPathPart::Field(field) => value.get_mut(field),
+            JsonPathPart::Index(index) => value.get_mut(index),
+        })
     }
 }
 impl Display for JsonPath {
@@ -38,28 +63,215 @@ impl Display for JsonPath {
         write!(f, "{}", out)
     }
 }
-pub fn crawl_json<F>(value: &Value, path: JsonPath, predicate: &F, out: &mut Vec<(JsonPath, i64)>)
+/// Recognizes the `{"seconds": ..., "nanos": ...}` shape of `google.protobuf.Timestamp`.
+/// `seconds` may be a number or a string (int64 values are quoted in proto JSON).
+fn proto_timestamp(obj: &serde_json::Map<String, Value>) -> Option<Timestamp> {
+    if obj.keys().any(|key| key != "seconds" && key != "nanos") {
+        return None;
+    }
+    let secs = match obj.get("seconds")? {
+        Value::Number(num) => num.as_i64()?,
+        Value::String(s) => s.parse().ok()?,
+        _ => return None,
+    };
+    let nanos = match obj.get("nanos") {
+        Some(nanos) => u32::try_from(nanos.as_i64()?).ok()?,
+        None => 0,
+    };
+    Timestamp::new(secs, nanos)
+}
+
+/// How a hit's raw value was turned into a timestamp.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Interpretation {
+    /// A number (or a string holding one) counting units since an epoch.
+    Epoch(EpochFamily),
+    ProtoTimestamp,
+    /// A date string, parsed with this format (see `parse_formatted`).
+    Format(String),
+}
+
+impl Display for Interpretation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Interpretation::Epoch(family) => write!(f, "{}", family.name),
+            Interpretation::ProtoTimestamp => write!(f, "Protobuf Timestamp"),
+            Interpretation::Format(format) => write!(f, "Format {}", format),
+        }
+    }
+}
+
+/// The kind of JSON value a hit was found in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SourceKind {
+    Number,
+    String,
+    /// A `{"seconds": ..., "nanos": ...}` object.
+    Object,
+    /// A value in a decoded JWT (see `ParsedInput::mark_jwt_hits`), rather than in the text.
+    Jwt,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Hit {
+    pub path: JsonPath,
+    /// The value as it appears in the JSON.
+    pub raw: String,
+    pub ts: Timestamp,
+    pub interpretation: Interpretation,
+    pub source: SourceKind,
+    /// How likely this is to really be a timestamp, from 0 to 1. Filled in by `score_hits`.
+    pub confidence: f32,
+    /// Whether the interpretation comes from a user's rule rather than from the heuristics.
+    pub pinned: bool,
+    /// Where the value is in the text, when scanned from text (see `locate_hits`).
+    pub span: Option<SourceSpan>,
+}
+
+/// Interprets a number using the first family (in the given order) whose result satisfies the predicate.
+fn number_to_timestamp<F>(
+    num: &serde_json::Number,
+    families: &[EpochFamily],
+    predicate: &F,
+) -> Option<(Timestamp, EpochFamily)>
 where
     F: Fn(i64) -> bool,
 {
+    families.iter().find_map(|family| {
+        let ts = match num.as_i64() {
+            Some(num) => family.timestamp_from_int(num),
+            None => family.timestamp_from_float(num.as_f64()?),
+        }?;
+        predicate(ts.secs).then_some((ts, *family))
+    })
+}
+
+/// Looks for timestamps in `value`. Numbers (and strings holding numbers) are tried against
+/// `families`, other strings against `formats`.
+///
+/// The user's `rules` come first. A path pinned to a family is reported with it, even outside
+/// the predicate. A path marked as not a timestamp is skipped, with everything inside it.
+/// Protobuf Timestamp objects have no family, so family rules don't apply to them.
+pub fn crawl_json<F>(
+    value: &Value,
+    path: JsonPath,
+    families: &[EpochFamily],
+    formats: &[String],
+    rules: &[Rule],
+    predicate: &F,
+    out: &mut Vec<Hit>,
+) where
+    F: Fn(i64) -> bool,
+{
+    let rule = find_rule(rules, &path).map(|rule| rule.action);
+    if rule == Some(RuleAction::NotATimestamp) {
+        return;
+    }
+    let interpret_number = |num: &serde_json::Number| match rule {
+        Some(RuleAction::Family(family)) => {
+            number_to_timestamp(num, &[family], &|_| true).map(|found| (found, true))
+        }
+        _ => number_to_timestamp(num, families, predicate).map(|found| (found, false)),
+    };
     match value {
         Value::Number(num) => {
-            if let Some(num) = num.as_i64() {
-                if predicate(num) {
-                    out.push((path, num))
+            if let Some(((ts, family), pinned)) = interpret_number(num) {
+                out.push(Hit {
+                    path,
+                    raw: num.to_string(),
+                    ts,
+                    interpretation: Interpretation::Epoch(family),
+                    source: SourceKind::Number,
+                    confidence: 0.0,
+                    pinned,
+                    span: None,
+                })
+            }
+        }
+        Value::String(s) => {
+            let found = match s.parse::<serde_json::Number>() {
+                Ok(num) => interpret_number(&num)
+                    .map(|((ts, family), pinned)| (ts, Interpretation::Epoch(family), pinned)),
+                Err(_) => formats.iter().find_map(|format| {
+                    let ts = parse_formatted(s, format)?;
+                    predicate(ts.secs).then(|| (ts, Interpretation::Format(format.clone()), false))
+                }),
+            };
+            if let Some((ts, interpretation, pinned)) = found {
+                out.push(Hit {
+                    path,
+                    raw: value.to_string(),
+                    ts,
+                    interpretation,
+                    source: SourceKind::String,
+                    confidence: 0.0,
+                    pinned,
+                    span: None,
+                })
+            }
+        }
+        Value::Array(arr) => {
+            for (i, sub_val) in arr.iter().enumerate() {
+                let sub_path = path.append(JsonPathPart::Index(i));
+                crawl_json(sub_val, sub_path, families, formats, rules, predicate, out);
+            }
+        }
+        Value::Object(obj) => {
+            if let Some(ts) = proto_timestamp(obj) {
+                if predicate(ts.secs) {
+                    out.push(Hit {
+                        path,
+                        raw: ts.to_string(),
+                        ts,
+                        interpretation: Interpretation::ProtoTimestamp,
+                        source: SourceKind::Object,
+                        confidence: 0.0,
+                        pinned: false,
+                        span: None,
+                    });
+                    return;
                 }
             }
+            for (key, sub_val) in obj.into_iter() {
+                let sub_path = path.append(JsonPathPart::Field(key.clone()));
+                crawl_json(sub_val, sub_path, families, formats, rules, predicate, out)
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Parses a JSON document, or a stream of them (e.g. NDJSON logs) as an array.
+pub fn parse_json_stream(text: &str) -> Result<Value, serde_json::Error> {
+    let mut values = serde_json::Deserializer::from_str(text)
+        .into_iter::<Value>()
+        .collect::<Result<Vec<_>, _>>()?;
+    match values.len() {
+        // An empty document is still an error.
+        0 => serde_json::from_str(text),
+        1 => Ok(values.remove(0)),
+        _ => Ok(Value::Array(values)),
+    }
+}
+
+/// Collects string values in the `google.protobuf.Duration` JSON form (e.g. "3.5s").
+pub fn crawl_durations(value: &Value, path: JsonPath, out: &mut Vec<(JsonPath, Duration)>) {
+    match value {
+        Value::String(s) => {
+            if let Some(duration) = parse_proto_duration(s) {
+                out.push((path, duration))
+            }
         }
         Value::Array(arr) => {
             for (i, sub_val) in arr.iter().enumerate() {
                 let sub_path = path.append(JsonPathPart::Index(i));
-                crawl_json(sub_val, sub_path, predicate, out);
+                crawl_durations(sub_val, sub_path, out);
             }
         }
         Value::Object(obj) => {
             for (key, sub_val) in obj.into_iter() {
                 let sub_path = path.append(JsonPathPart::Field(key.clone()));
-                crawl_json(sub_val, sub_path, predicate, out)
+                crawl_durations(sub_val, sub_path, out)
             }
         }
         _ => {}
@@ -69,6 +281,19 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::datetime::EPOCH_FAMILIES;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_json_stream() {
+        assert_eq!(parse_json_stream(" {\"a\": 1} ").unwrap(), json!({"a": 1}));
+        assert_eq!(
+            parse_json_stream("{\"a\": 1}\n{\"a\": 2}\n").unwrap(),
+            json!([{"a": 1}, {"a": 2}])
+        );
+        assert!(parse_json_stream("").is_err());
+        assert!(parse_json_stream("{\"a\": 1}\n{\"a\"").is_err());
+    }
 
     #[test]
     fn test() {
@@ -96,38 +321,214 @@ mod tests {
         let value = serde_json::from_str(data).unwrap();
         let mut out = vec![];
         let predicate = |num| num > 8;
-        crawl_json(&value, JsonPath::new(), &predicate, &mut out);
+        crawl_json(
+            &value,
+            JsonPath::new(),
+            &EPOCH_FAMILIES[..1],
+            &[],
+            &[],
+            &predicate,
+            &mut out,
+        );
     }
 
     #[test]
-    fn text_is_subset_off() {
-        let x = JsonPath(vec![
-            JsonPathPart::Field("a".to_owned()),
-            JsonPathPart::Field("b".to_owned()),
-            JsonPathPart::Field("c".to_owned()),
-        ]);
-        let y = JsonPath(vec![
-            JsonPathPart::Field("a".to_owned()),
-            JsonPathPart::Field("b".to_owned()),
-        ]);
-        let z = JsonPath(vec![
-            JsonPathPart::Field("a".to_owned()),
-            JsonPathPart::Field("b".to_owned()),
-            JsonPathPart::Index(13),
-        ]);
-        let w = JsonPath::new();
-
-        assert!(y.is_prefix_of(&x));
-        assert!(y.is_prefix_of(&z));
-        assert!(!x.is_prefix_of(&y));
-        assert!(!x.is_prefix_of(&z));
-        assert!(!z.is_prefix_of(&x));
-        assert!(!z.is_prefix_of(&y));
-        assert!(w.is_prefix_of(&w));
-        for path in [&x, &y, &z] {
-            assert!(path.is_prefix_of(path));
-            assert!(w.is_prefix_of(path));
-            assert!(!path.is_prefix_of(&w));
-        }
+    fn test_proto_timestamp() {
+        let data = r#"
+        {
+            "created": {"seconds": "1692694500", "nanos": 123000000},
+            "updated": {"seconds": 1692694600},
+            "not_a_ts": {"seconds": 1692694600, "other": 1},
+            "timeout": "3.5s"
+        }"#;
+        let value = serde_json::from_str(data).unwrap();
+        let mut out = vec![];
+        let predicate = |num| num > 1_000_000_000;
+        crawl_json(
+            &value,
+            JsonPath::new(),
+            &EPOCH_FAMILIES[..1],
+            &[],
+            &[],
+            &predicate,
+            &mut out,
+        );
+        let field = |name: &str| JsonPath(vec![JsonPathPart::Field(name.to_owned())]);
+        let unix_seconds = Interpretation::Epoch(EPOCH_FAMILIES[0]);
+        assert_eq!(
+            out,
+            vec![
+                Hit {
+                    path: field("created"),
+                    raw: "1692694500.123000000".to_owned(),
+                    ts: Timestamp::new(1692694500, 123000000).unwrap(),
+                    interpretation: Interpretation::ProtoTimestamp,
+                    source: SourceKind::Object,
+                    confidence: 0.0,
+                    pinned: false,
+                    span: None,
+                },
+                Hit {
+                    path: field("not_a_ts").append(JsonPathPart::Field("seconds".to_owned())),
+                    raw: "1692694600".to_owned(),
+                    ts: Timestamp::from_secs(1692694600),
+                    interpretation: unix_seconds,
+                    source: SourceKind::Number,
+                    confidence: 0.0,
+                    pinned: false,
+                    span: None,
+                },
+                Hit {
+                    path: field("updated"),
+                    raw: "1692694600".to_owned(),
+                    ts: Timestamp::from_secs(1692694600),
+                    interpretation: Interpretation::ProtoTimestamp,
+                    source: SourceKind::Object,
+                    confidence: 0.0,
+                    pinned: false,
+                    span: None,
+                },
+            ]
+        );
+
+        let mut durations = vec![];
+        crawl_durations(&value, JsonPath::new(), &mut durations);
+        assert_eq!(
+            durations,
+            vec![(field("timeout"), Duration::milliseconds(3500))]
+        );
+    }
+
+    #[test]
+    fn test_epoch_families() {
+        let data = r#"{"ms": 1692694500123, "filetime": 133371681000000000, "excel": 45160.375, "id": 42}"#;
+        let value = serde_json::from_str(data).unwrap();
+        let (min_ts, max_ts) = (1_600_000_000, 1_800_000_000);
+        let predicate = |ts| (ts >= min_ts) && (ts <= max_ts);
+        let mut out = vec![];
+        crawl_json(
+            &value,
+            JsonPath::new(),
+            EPOCH_FAMILIES,
+            &[],
+            &[],
+            &predicate,
+            &mut out,
+        );
+        let found: Vec<_> = out
+            .iter()
+            .map(|hit| (hit.path.to_string(), hit.interpretation.to_string()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("excel".to_owned(), "Excel serial (1900)".to_owned()),
+                ("filetime".to_owned(), "Windows FILETIME / LDAP".to_owned()),
+                ("ms".to_owned(), "Unix milliseconds".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rules() {
+        let data = r#"{"id": 1692694500, "events": [{"t": 1692694500123}, {"t": 5}],
+            "p": {"seconds": 1692694500}, "q": {"seconds": 1692694500}}"#;
+        let value = serde_json::from_str(data).unwrap();
+        let predicate = |ts| ts > 1_000_000_000;
+        let rules = vec![
+            Rule {
+                pattern: "id".parse().unwrap(),
+                action: RuleAction::NotATimestamp,
+            },
+            Rule {
+                pattern: "events[*].t".parse().unwrap(),
+                action: RuleAction::Family(EpochFamily::by_name("Unix milliseconds").unwrap()),
+            },
+            // Nothing inside it is reported either, e.g. `p.seconds` as Unix seconds.
+            Rule {
+                pattern: "p".parse().unwrap(),
+                action: RuleAction::NotATimestamp,
+            },
+            // Ignored: a protobuf Timestamp has no family.
+            Rule {
+                pattern: "q".parse().unwrap(),
+                action: RuleAction::Family(EpochFamily::by_name("Unix milliseconds").unwrap()),
+            },
+        ];
+        let mut out = vec![];
+        crawl_json(
+            &value,
+            JsonPath::new(),
+            &EPOCH_FAMILIES[..1],
+            &[],
+            &rules,
+            &predicate,
+            &mut out,
+        );
+        let found: Vec<_> = out
+            .iter()
+            .map(|hit| (hit.path.to_string(), hit.ts, hit.pinned))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "events[0].t".to_owned(),
+                    Timestamp::new(1692694500, 123_000_000).unwrap(),
+                    true
+                ),
+                (
+                    "events[1].t".to_owned(),
+                    Timestamp::new(0, 5_000_000).unwrap(),
+                    true
+                ),
+                ("q".to_owned(), Timestamp::from_secs(1692694500), false),
+            ]
+        );
+        assert_eq!(out[2].interpretation, Interpretation::ProtoTimestamp);
+    }
+
+    #[test]
+    fn test_strings() {
+        let data = r#"{"quoted": "1692694500123", "iso": "2023-08-22T08:55:00.5Z", "day": "2023-08-22", "name": "bob"}"#;
+        let value = serde_json::from_str(data).unwrap();
+        let predicate = |ts| (1_600_000_000..1_800_000_000).contains(&ts);
+        let formats = vec!["rfc3339".to_owned(), "%Y-%m-%d".to_owned()];
+        let mut out = vec![];
+        crawl_json(
+            &value,
+            JsonPath::new(),
+            &EPOCH_FAMILIES[..2],
+            &formats,
+            &[],
+            &predicate,
+            &mut out,
+        );
+        let found: Vec<_> = out
+            .iter()
+            .map(|hit| (hit.path.to_string(), hit.ts, hit.interpretation.to_string()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "day".to_owned(),
+                    Timestamp::from_secs(1692662400),
+                    "Format %Y-%m-%d".to_owned()
+                ),
+                (
+                    "iso".to_owned(),
+                    Timestamp::new(1692694500, 500_000_000).unwrap(),
+                    "Format rfc3339".to_owned()
+                ),
+                (
+                    "quoted".to_owned(),
+                    Timestamp::new(1692694500, 123_000_000).unwrap(),
+                    "Unix milliseconds".to_owned()
+                ),
+            ]
+        );
+        assert!(out.iter().all(|hit| hit.source == SourceKind::String));
+        assert_eq!(out[2].raw, "\"1692694500123\"");
     }
 }
@@ -0,0 +1,263 @@
+use std::ops::Range;
+
+use crate::json_crawl::parse_json_stream;
+
+/// What a piece of JSON text is.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TokenKind {
+    /// An object key, with its quotes.
+    Key,
+    /// Any other string, with its quotes.
+    String,
+    Number,
+    /// `true`, `false` or `null`.
+    Literal,
+    /// One of `{}[],:`.
+    Punctuation,
+    /// Anything else, e.g. a word while it's being typed.
+    Invalid,
+}
+
+/// Whether `byte` ends a number or a literal.
+fn is_delimiter(byte: u8) -> bool {
+    byte.is_ascii_whitespace() || matches!(byte, b'{' | b'}' | b'[' | b']' | b',' | b':' | b'"')
+}
+
+/// Splits JSON text into tokens, skipping whitespace. Text that isn't valid JSON (e.g. while
+/// it's typed) still splits: an unterminated string ends at the end of its line, and other
+/// stray text is `Invalid`.
+pub fn tokenize(text: &str) -> Vec<(TokenKind, Range<usize>)> {
+    let bytes = text.as_bytes();
+    let mut tokens = vec![];
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let start = pos;
+        let kind = match bytes[pos] {
+            byte if byte.is_ascii_whitespace() => {
+                pos += 1;
+                continue;
+            }
+            b'{' | b'}' | b'[' | b']' | b',' | b':' => {
+                pos += 1;
+                TokenKind::Punctuation
+            }
+            b'"' => {
+                pos += 1;
+                while pos < bytes.len() && !matches!(bytes[pos], b'"' | b'\n') {
+                    pos += if bytes[pos] == b'\\' { 2 } else { 1 };
+                }
+                pos = (pos + 1).min(bytes.len());
+                let next = bytes[pos..].iter().find(|byte| !byte.is_ascii_whitespace());
+                match next {
+                    Some(b':') => TokenKind::Key,
+                    _ => TokenKind::String,
+                }
+            }
+            _ => {
+                while pos < bytes.len() && !is_delimiter(bytes[pos]) {
+                    pos += 1;
+                }
+                match &text[start..pos] {
+                    "true" | "false" | "null" => TokenKind::Literal,
+                    word if word.parse::<serde_json::Number>().is_ok() => TokenKind::Number,
+                    _ => TokenKind::Invalid,
+                }
+            }
+        };
+        tokens.push((kind, start..pos));
+    }
+    tokens
+}
+
+/// Lays the tokens of a JSON document, or stream of them, out: indented by `indent`, or on a
+/// single line if it's `None`. Values are copied as they are written. A stream's documents go
+/// on lines of their own.
+fn layout(text: &str, tokens: &[Range<usize>], indent: Option<&str>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut depth = 0;
+    let new_line = |out: &mut String, depth: usize| {
+        if let Some(indent) = indent {
+            out.push('\n');
+            out.push_str(&indent.repeat(depth));
+        }
+    };
+    for (i, range) in tokens.iter().enumerate() {
+        let token = &text[range.clone()];
+        let next = tokens.get(i + 1).map(|range| &text[range.clone()]);
+        match token {
+            "{" | "[" => {
+                out.push_str(token);
+                if !matches!(next, Some("}" | "]")) {
+                    depth += 1;
+                    new_line(&mut out, depth);
+                }
+            }
+            "}" | "]" => {
+                let previous = i.checked_sub(1).map(|i| &text[tokens[i].clone()]);
+                if !matches!(previous, Some("{" | "[")) {
+                    depth -= 1;
+                    new_line(&mut out, depth);
+                }
+                out.push_str(token);
+            }
+            "," => {
+                out.push(',');
+                new_line(&mut out, depth);
+            }
+            ":" => out.push_str(if indent.is_some() { ": " } else { ":" }),
+            _ => out.push_str(token),
+        }
+        // The end of one of a stream's documents.
+        if depth == 0 && next.is_some() && !matches!(token, "{" | "[" | ":" | ",") {
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// The tokens of JSON text that's known to parse, without their kinds.
+fn token_ranges(text: &str) -> Vec<Range<usize>> {
+    tokenize(text).into_iter().map(|(_, range)| range).collect()
+}
+
+/// Lays a JSON document, or stream of them, out again (see `layout`), keeping its key order.
+fn reindent(text: &str, indent: Option<&str>) -> Result<String, String> {
+    parse_json_stream(text).map_err(|err| err.to_string())?;
+    Ok(layout(text, &token_ranges(text), indent))
+}
+
+/// Pushes the tokens of the value starting at `tokens[i]` to `out`, with the members of every
+/// object in it sorted by key, and returns the index after the value. Keys are compared
+/// unescaped, as serde_json sorts them, and duplicates keep their order.
+fn sort_value(text: &str, tokens: &[Range<usize>], i: usize, out: &mut Vec<Range<usize>>) -> usize {
+    let token = |i: usize| &text[tokens[i].clone()];
+    match token(i) {
+        "{" => {
+            out.push(tokens[i].clone());
+            let mut members = vec![];
+            let mut commas = vec![];
+            let mut i = i + 1;
+            while token(i) != "}" {
+                let key: String = serde_json::from_str(token(i)).unwrap_or_default();
+                // The key and the colon.
+                let mut member = vec![tokens[i].clone(), tokens[i + 1].clone()];
+                i = sort_value(text, tokens, i + 2, &mut member);
+                members.push((key, member));
+                if token(i) == "," {
+                    commas.push(tokens[i].clone());
+                    i += 1;
+                }
+            }
+            members.sort_by(|(a, _), (b, _)| a.cmp(b));
+            let mut commas = commas.into_iter();
+            for (j, (_, member)) in members.into_iter().enumerate() {
+                if j > 0 {
+                    out.extend(commas.next());
+                }
+                out.extend(member);
+            }
+            out.push(tokens[i].clone());
+            i + 1
+        }
+        "[" => {
+            out.push(tokens[i].clone());
+            let mut i = i + 1;
+            while token(i) != "]" {
+                i = sort_value(text, tokens, i, out);
+                if token(i) == "," {
+                    out.push(tokens[i].clone());
+                    i += 1;
+                }
+            }
+            out.push(tokens[i].clone());
+            i + 1
+        }
+        _ => {
+            out.push(tokens[i].clone());
+            i + 1
+        }
+    }
+}
+
+/// Pretty-prints JSON text, keeping its key order and how its values are written.
+pub fn format_json(text: &str) -> Result<String, String> {
+    reindent(text, Some("  "))
+}
+
+/// Removes the whitespace from JSON text. A stream becomes NDJSON, one document per line.
+pub fn minify_json(text: &str) -> Result<String, String> {
+    reindent(text, None)
+}
+
+/// Sorts the keys of every object in JSON text, keeping how values are written. A document is
+/// laid out like `format_json` if it spans several lines, and like `minify_json` if not. A
+/// stream becomes NDJSON.
+pub fn sort_keys(text: &str) -> Result<String, String> {
+    parse_json_stream(text).map_err(|err| err.to_string())?;
+    let tokens = token_ranges(text);
+    let mut sorted = Vec::with_capacity(tokens.len());
+    let (mut i, mut documents) = (0, 0);
+    while i < tokens.len() {
+        i = sort_value(text, &tokens, i, &mut sorted);
+        documents += 1;
+    }
+    let indent = (documents == 1 && text.trim().contains('\n')).then_some("  ");
+    Ok(layout(text, &sorted, indent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_json() {
+        let text = r#"{"b": [1.0e3, {}, []], "a": {"s": "x\"y"}}"#;
+        assert_eq!(
+            format_json(text).unwrap(),
+            "{\n  \"b\": [\n    1.0e3,\n    {},\n    []\n  ],\n  \"a\": {\n    \"s\": \"x\\\"y\"\n  }\n}"
+        );
+        assert_eq!(
+            minify_json(&format_json(text).unwrap()).unwrap(),
+            text.replace(' ', "")
+        );
+        assert_eq!(
+            sort_keys(text).unwrap(),
+            r#"{"a":{"s":"x\"y"},"b":[1.0e3,{},[]]}"#
+        );
+        let unsorted =
+            r#"{"z": 123456789012345678901234567890, "b": [{"y": 1, "x": 2}], "\u0061": 0}"#;
+        assert_eq!(
+            sort_keys(&format_json(unsorted).unwrap()).unwrap(),
+            format_json(
+                r#"{"\u0061": 0, "b": [{"x": 2, "y": 1}], "z": 123456789012345678901234567890}"#
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            sort_keys("{\"b\": 1, \"a\": 2}\n{\"d\": {}, \"c\": []}").unwrap(),
+            "{\"a\":2,\"b\":1}\n{\"c\":[],\"d\":{}}"
+        );
+        assert_eq!(
+            minify_json("{\"t\": 1}\n\n  {\"t\": 2} 3").unwrap(),
+            "{\"t\":1}\n{\"t\":2}\n3"
+        );
+        assert!(format_json("{\"a\": }").is_err());
+
+        let kinds: Vec<_> = tokenize("{\"k\" : tru, \"v\nnull")
+            .into_iter()
+            .map(|(kind, _)| kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                TokenKind::Punctuation,
+                TokenKind::Key,
+                TokenKind::Punctuation,
+                TokenKind::Invalid,
+                TokenKind::Punctuation,
+                TokenKind::String,
+                TokenKind::Literal,
+            ]
+        );
+    }
+}
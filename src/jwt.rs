@@ -0,0 +1,249 @@
+use serde_json::{Map, Value};
+
+use crate::json_crawl::{parse_json_stream, Hit, JsonPath, JsonPathPart, SourceKind};
+
+/// The registered claims holding times (RFC 7519), and what they mean.
+pub const TIME_CLAIMS: &[(&str, &str)] = &[
+    ("exp", "expires"),
+    ("iat", "issued at"),
+    ("nbf", "not before"),
+    ("auth_time", "authenticated at"),
+];
+
+fn decode_base64url(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=');
+    if text.len() % 4 == 1 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut bits, mut bit_count) = (0u32, 0);
+    for c in text.bytes() {
+        let sextet = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'-' => 62,
+            b'_' => 63,
+            _ => return None,
+        };
+        bits = (bits << 6) | u32::from(sextet);
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            out.push((bits >> bit_count) as u8);
+            bits &= (1 << bit_count) - 1;
+        }
+    }
+    Some(out)
+}
+
+/// A JSON Web Token's header and payload. The signature isn't checked.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Jwt {
+    pub header: Map<String, Value>,
+    pub payload: Map<String, Value>,
+}
+
+impl Jwt {
+    /// Decodes a compact JWT (`header.payload.signature`), or returns `None` if it isn't one.
+    pub fn decode(token: &str) -> Option<Self> {
+        let mut parts = token.split('.');
+        let (header, payload, _signature) = (parts.next()?, parts.next()?, parts.next()?);
+        // Every JWT header is a JSON object, so it starts with `{"`, which encodes to "eyJ".
+        if parts.next().is_some() || !header.starts_with("eyJ") {
+            return None;
+        }
+        let object = |part: &str| match serde_json::from_slice(&decode_base64url(part)?) {
+            Ok(Value::Object(object)) => Some(object),
+            _ => None,
+        };
+        Some(Jwt {
+            header: object(header)?,
+            payload: object(payload)?,
+        })
+    }
+
+    pub fn to_value(&self) -> Value {
+        serde_json::json!({"header": self.header, "payload": self.payload})
+    }
+
+    /// Time claims that contradict each other, or aren't numbers.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        let mut claim = |name: &str| match self.payload.get(name) {
+            Some(Value::Number(number)) => number.as_f64(),
+            Some(_) => {
+                problems.push(format!("{} is not a number", name));
+                None
+            }
+            None => None,
+        };
+        let (exp, iat, nbf, auth_time) =
+            (claim("exp"), claim("iat"), claim("nbf"), claim("auth_time"));
+        let checks = [
+            (nbf, exp, "nbf is after exp, so it's never valid"),
+            (
+                iat,
+                exp,
+                "iat is after exp, so it expired before it was issued",
+            ),
+            (auth_time, iat, "auth_time is after iat"),
+            (auth_time, exp, "auth_time is after exp"),
+        ];
+        for (earlier, later, problem) in checks {
+            if let (Some(earlier), Some(later)) = (earlier, later) {
+                if earlier > later {
+                    problems.push(problem.to_owned());
+                }
+            }
+        }
+        problems
+    }
+}
+
+/// Replaces every string in `value` that holds a JWT with its decoded `{header, payload}`, and
+/// returns their paths and problems.
+pub fn expand_jwts(value: &mut Value) -> Vec<(JsonPath, Vec<String>)> {
+    fn expand(value: &mut Value, path: &mut JsonPath, found: &mut Vec<(JsonPath, Vec<String>)>) {
+        match value {
+            Value::String(text) => {
+                if let Some(jwt) = Jwt::decode(text.trim()) {
+                    found.push((path.clone(), jwt.problems()));
+                    *value = jwt.to_value();
+                }
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter_mut().enumerate() {
+                    path.0.push(JsonPathPart::Index(i));
+                    expand(item, path, found);
+                    path.0.pop();
+                }
+            }
+            Value::Object(object) => {
+                for (key, item) in object {
+                    path.0.push(JsonPathPart::Field(key.clone()));
+                    expand(item, path, found);
+                    path.0.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut found = vec![];
+    expand(value, &mut JsonPath::new(), &mut found);
+    found
+}
+
+/// A document read by `parse_input`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ParsedInput {
+    /// The JSON, with every JWT replaced by its decoded `{header, payload}`.
+    pub value: Value,
+    /// Where each JWT was, and its problems (see `Jwt::problems`).
+    pub jwts: Vec<(JsonPath, Vec<String>)>,
+}
+
+impl ParsedInput {
+    /// The JWTs' problems, each prefixed with the JWT's path.
+    pub fn problems(&self) -> Vec<String> {
+        self.jwts
+            .iter()
+            .flat_map(|(path, problems)| {
+                let token = match path.0.is_empty() {
+                    true => "JWT".to_owned(),
+                    false => path.to_string(),
+                };
+                problems
+                    .iter()
+                    .map(move |problem| format!("{}: {}", token, problem))
+            })
+            .collect()
+    }
+
+    /// Marks the hits found in `value` that are inside a decoded JWT as `SourceKind::Jwt`.
+    pub fn mark_jwt_hits(&self, hits: &mut [Hit]) {
+        for hit in hits {
+            if self
+                .jwts
+                .iter()
+                .any(|(path, _)| hit.path.0.starts_with(&path.0))
+            {
+                hit.source = SourceKind::Jwt;
+            }
+        }
+    }
+}
+
+/// Parses a document: JSON or newline-delimited JSON (see `parse_json_stream`), or a bare JWT.
+/// JWTs are replaced by their decoded `{header, payload}`.
+pub fn parse_input(text: &str) -> Result<ParsedInput, String> {
+    let mut value = match parse_json_stream(text) {
+        Ok(value) => value,
+        Err(err) => match Jwt::decode(text.trim()) {
+            Some(_) => Value::String(text.trim().to_owned()),
+            None => return Err(err.to_string()),
+        },
+    };
+    let jwts = expand_jwts(&mut value);
+    Ok(ParsedInput { value, jwts })
+}
+
+/// What a registered time claim means, if the hit is one in a decoded JWT's payload.
+pub fn claim_label(hit: &Hit) -> Option<&'static str> {
+    if hit.source != SourceKind::Jwt {
+        return None;
+    }
+    match hit.path.0.as_slice() {
+        [.., JsonPathPart::Field(payload), JsonPathPart::Field(claim)] if payload == "payload" => {
+            TIME_CLAIMS
+                .iter()
+                .find(|(name, _)| name == claim)
+                .map(|(_, label)| *label)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+
+    const TOKEN: &str = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.\
+        eyJzdWIiOiIxIiwiaWF0IjoxNjkyNjk0NTAwLCJuYmYiOjE2OTI2OTgxMDAsImV4cCI6MTY5MjY5NzAwMH0.c2ln";
+
+    #[test]
+    fn test_jwt() {
+        let jwt = Jwt::decode(TOKEN).unwrap();
+        assert_eq!(jwt.header["alg"], "HS256");
+        assert_eq!(jwt.payload["exp"], 1692697000);
+        assert_eq!(jwt.problems(), ["nbf is after exp, so it's never valid"]);
+        assert_eq!(Jwt::decode("a.b.c"), None);
+
+        let input = parse_input(&format!(" {} ", TOKEN)).unwrap();
+        assert_eq!(input.value["payload"]["iat"], 1692694500);
+        assert_eq!(
+            input.problems(),
+            ["JWT: nbf is after exp, so it's never valid"]
+        );
+
+        let text = format!(
+            r#"{{"auth": ["{}"], "payload": {{"exp": 1692697000}}}}"#,
+            TOKEN
+        );
+        let input = parse_input(&text).unwrap();
+        let problems = input.problems();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("auth[0]: "));
+        let mut hits = Scanner::new().years(2020, 2030).scan(&input.value);
+        input.mark_jwt_hits(&mut hits);
+        let label = |path: &str| {
+            let hit = hits.iter().find(|hit| hit.path.to_string() == path);
+            claim_label(hit.unwrap())
+        };
+        assert_eq!(label("auth[0].payload.exp"), Some("expires"));
+        // Plain JSON shaped like a JWT's payload isn't one.
+        assert_eq!(label("payload.exp"), None);
+        assert!(parse_input("not json").is_err());
+    }
+}
@@ -1,7 +1,57 @@
 #![warn(clippy::all, rust_2018_idioms)]
+#![cfg(feature = "core")]
 
+mod anchors;
+mod annotate;
+#[cfg(feature = "gui")]
 mod app;
+#[cfg(all(feature = "gui", not(target_arch = "wasm32")))]
+mod clipboard;
+mod confidence;
 mod datetime;
+#[cfg(feature = "gui")]
+mod documents;
+#[cfg(feature = "gui")]
+mod editor;
+mod expiry;
+mod export;
+#[cfg(feature = "gui")]
+mod files;
+mod gaps;
 mod json_crawl;
-mod tree;
+mod json_text;
+mod jwt;
+mod normalize;
+#[cfg(feature = "gui")]
+mod permalink;
+mod profile;
+mod rewrite;
+mod rules;
+mod scanner;
+mod spans;
+#[cfg(feature = "wasm")]
+mod wasm_api;
+mod watch;
+pub use anchors::{resolve_anchors, Anchor, AnchorSource};
+pub use annotate::annotate;
+#[cfg(feature = "gui")]
 pub use app::TemplateApp;
+pub use datetime::{
+    duration_to_str, format_like, parse_date, parse_duration, parse_formatted, timestamp_to_str,
+    ts_to_str, DurationStyle, EpochFamily, Timestamp, Tz, DURATION_STYLES, EPOCH_FAMILIES,
+};
+pub use expiry::ExpiryFilter;
+pub use export::hits_to_csv;
+#[cfg(feature = "gui")]
+pub use files::OpenedFile;
+pub use gaps::{deltas, interval_stats, IntervalStats};
+pub use json_crawl::{parse_json_stream, Hit, Interpretation, JsonPath, JsonPathPart, SourceKind};
+pub use json_text::{format_json, minify_json, sort_keys, tokenize, TokenKind};
+pub use jwt::{claim_label, expand_jwts, parse_input, Jwt, ParsedInput, TIME_CLAIMS};
+pub use normalize::{NormalizeMode, Normalizer, NORMALIZE_MODES};
+pub use profile::{check_years, Profile, Settings, PROFILE_VERSION};
+pub use rewrite::{edit_hit, encode_like, rewrite_hits, shift_text, Shift};
+pub use rules::{add_rule, PathPattern, PatternPart, Rule, RuleAction};
+pub use scanner::Scanner;
+pub use spans::{find_span, locate_hits, visit_spans, SourceSpan};
+pub use watch::{FileWatcher, SeenHits};
@@ -1,16 +1,58 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
+/// What the command line asks for: `[--profile <file>] [file]`.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct Args {
+    profile: Option<json_unix_time::Profile>,
+    file: Option<json_unix_time::OpenedFile>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut parsed = Args::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--profile" => {
+                let path = args.next().ok_or("--profile requires a file")?;
+                parsed.profile = Some(json_unix_time::Profile::from_file(&path)?);
+            }
+            _ if arg.starts_with('-') => return Err(format!("unexpected option {:?}", arg)),
+            _ if parsed.file.is_none() => {
+                parsed.file = Some(json_unix_time::OpenedFile::read(arg.into())?);
+            }
+            _ => return Err(format!("unexpected argument {:?}", arg)),
+        }
+    }
+    Ok(parsed)
+}
+
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result<()> {
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
 
+    let args = parse_args().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(2);
+    });
+
     let native_options = eframe::NativeOptions::default();
     eframe::run_native(
         "eframe template",
         native_options,
-        Box::new(|cc| Box::new(json_unix_time::TemplateApp::new(cc))),
+        Box::new(|cc| {
+            let mut app = json_unix_time::TemplateApp::new(cc);
+            if let Some(profile) = args.profile {
+                app.apply_profile(profile);
+            }
+            if let Some(file) = args.file {
+                app.open_file(file);
+            }
+            Box::new(app)
+        }),
     )
 }
 
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use chrono::Duration;
+
+use crate::datetime::Timestamp;
+use crate::json_crawl::Hit;
+use crate::rewrite::rewrite_hits;
+use crate::scanner::Scanner;
+
+/// What normalized timestamps are replaced with.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum NormalizeMode {
+    /// The base time, for all of them.
+    #[default]
+    Fixed,
+    /// The base time plus a step for each earlier distinct time, so their order is kept.
+    Sequential,
+    /// The base time plus their offset from the earliest.
+    Relative,
+}
+
+pub const NORMALIZE_MODES: &[NormalizeMode] = &[
+    NormalizeMode::Fixed,
+    NormalizeMode::Sequential,
+    NormalizeMode::Relative,
+];
+
+impl NormalizeMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            NormalizeMode::Fixed => "fixed",
+            NormalizeMode::Sequential => "sequential",
+            NormalizeMode::Relative => "relative",
+        }
+    }
+}
+
+impl Display for NormalizeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for NormalizeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        NORMALIZE_MODES
+            .iter()
+            .find(|mode| mode.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = NORMALIZE_MODES.iter().map(|mode| mode.name()).collect();
+                format!(
+                    "unknown normalize mode {:?}, expected one of {:?}",
+                    s, names
+                )
+            })
+    }
+}
+
+/// Replaces timestamps with deterministic ones, e.g. so snapshot tests of an API don't break
+/// on every `created_at`. Each is written like the value it replaces (see `encode_like`).
+///
+/// ```
+/// use json_unix_time::{NormalizeMode, Normalizer, Scanner};
+///
+/// let scanner = Scanner::new().years(2020, 2030);
+/// let normalizer = Normalizer::new(NormalizeMode::Sequential);
+/// let text = r#"{"b": 1692694600, "a": 1692694500}"#;
+/// assert_eq!(
+///     normalizer.normalize_text(text, &scanner).unwrap(),
+///     r#"{"b": 946684801, "a": 946684800}"#
+/// );
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Normalizer {
+    mode: NormalizeMode,
+    base: Timestamp,
+    step: Duration,
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Self::new(NormalizeMode::default())
+    }
+}
+
+impl Normalizer {
+    /// Based at 2000-01-01T00:00:00Z, one second apart when sequential.
+    pub fn new(mode: NormalizeMode) -> Self {
+        Normalizer {
+            mode,
+            base: Timestamp::from_secs(946_684_800),
+            step: Duration::seconds(1),
+        }
+    }
+    /// The time the earliest timestamp becomes.
+    pub fn base(mut self, base: Timestamp) -> Self {
+        self.base = base;
+        self
+    }
+    /// How far apart sequential timestamps are.
+    pub fn step(mut self, step: Duration) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// The time each hit becomes, in the same order.
+    pub fn new_times(&self, hits: &[Hit]) -> Vec<Option<Timestamp>> {
+        let mut times: Vec<Timestamp> = hits.iter().map(|hit| hit.ts).collect();
+        times.sort();
+        times.dedup();
+        hits.iter()
+            .map(|hit| match self.mode {
+                NormalizeMode::Fixed => Some(self.base),
+                NormalizeMode::Sequential => {
+                    let rank = times.binary_search(&hit.ts).ok()?;
+                    let offset = self
+                        .step
+                        .num_nanoseconds()?
+                        .checked_mul(i64::try_from(rank).ok()?)?;
+                    self.base.checked_add(Duration::nanoseconds(offset))
+                }
+                NormalizeMode::Relative => self.base.checked_add(hit.ts.since(times[0])?),
+            })
+            .collect()
+    }
+
+    /// Normalizes the timestamps `scanner` finds in a JSON document or NDJSON stream, so its
+    /// rules and path filters decide which values are replaced. Returns the new text (see
+    /// `rewrite_hits`).
+    pub fn normalize_text(&self, text: &str, scanner: &Scanner) -> Result<String, String> {
+        let hits = scanner.scan_str(text).map_err(|err| err.to_string())?;
+        let new_times: HashMap<_, _> = hits
+            .iter()
+            .map(|hit| &hit.path)
+            .zip(self.new_times(&hits))
+            .collect();
+        rewrite_hits(text, &hits, |hit| new_times[&hit.path])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::PathPattern;
+
+    #[test]
+    fn test_normalize() {
+        let scanner = Scanner::new()
+            .years(2020, 2030)
+            .exclude("meta".parse::<PathPattern>().unwrap());
+        let text = r#"{"events": [{"at": "2023-08-22T08:55:10Z"}, {"at": "2023-08-22T08:55:00Z"},
+            {"at": "2023-08-22T08:55:10Z"}], "ms": "1692694500500", "meta": 1692694500}"#;
+        let normalized = |normalizer: Normalizer| {
+            let text = normalizer.normalize_text(text, &scanner).unwrap();
+            let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+            let times: Vec<_> = (0..3)
+                .map(|i| value["events"][i]["at"].as_str().unwrap().to_owned())
+                .collect();
+            (times, value["ms"].clone(), value["meta"].clone())
+        };
+
+        let (times, ms, meta) = normalized(Normalizer::new(NormalizeMode::Fixed));
+        assert!(times.iter().all(|time| time == "2000-01-01T00:00:00Z"));
+        assert_eq!((ms, meta), ("946684800000".into(), 1692694500.into()));
+
+        let step = Duration::minutes(1);
+        let (times, ms, _) = normalized(Normalizer::new(NormalizeMode::Sequential).step(step));
+        assert_eq!(
+            times,
+            [
+                "2000-01-01T00:02:00Z",
+                "2000-01-01T00:00:00Z",
+                "2000-01-01T00:02:00Z"
+            ]
+        );
+        assert_eq!(ms, "946684860000");
+
+        let base = Timestamp::from_secs(0);
+        let (times, ms, _) = normalized(Normalizer::new(NormalizeMode::Relative).base(base));
+        assert_eq!(times[0], "1970-01-01T00:00:10Z");
+        assert_eq!(ms, "500");
+        assert_eq!("relative".parse(), Ok(NormalizeMode::Relative));
+    }
+}
@@ -0,0 +1,87 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Where the web build lives, for links made by the native app.
+pub const PAGES_URL: &str = "https://tomshlomo.github.io/json-unix-time/";
+
+/// Longer links get truncated by some chat apps and browsers.
+pub const MAX_LINK_LEN: usize = 8000;
+
+const FRAGMENT_PREFIX: &str = "#state=";
+
+/// Stay well below what a link can hold, so a malicious link can't exhaust memory.
+const MAX_DECODED_LEN: usize = 64 * 1024 * 1024;
+
+/// A link to `base_url` that restores `state`, which is stored compressed in the fragment.
+pub fn encode<T: Serialize>(base_url: &str, state: &T) -> String {
+    let json = serde_json::to_vec(state).expect("app state is always serializable");
+    let compressed = miniz_oxide::deflate::compress_to_vec(&json, 9);
+    format!(
+        "{}{}{}",
+        base_url,
+        FRAGMENT_PREFIX,
+        base64::encode_config(compressed, base64::URL_SAFE_NO_PAD)
+    )
+}
+
+/// The state stored in a URL fragment by `encode`, or `None` if the fragment doesn't hold one.
+pub fn decode<T: DeserializeOwned>(fragment: &str) -> Option<Result<T, String>> {
+    let data = fragment.strip_prefix(FRAGMENT_PREFIX)?;
+    let decode = || {
+        let compressed = base64::decode_config(data, base64::URL_SAFE_NO_PAD)
+            .map_err(|err| format!("bad link: {}", err))?;
+        let json = miniz_oxide::inflate::decompress_to_vec_with_limit(&compressed, MAX_DECODED_LEN)
+            .map_err(|err| format!("bad link: {:?}", err.status))?;
+        serde_json::from_slice(&json).map_err(|err| format!("bad link: {}", err))
+    };
+    Some(decode())
+}
+
+/// The page to link to: the current one on the web, the GitHub Pages build otherwise.
+pub fn page_url() -> String {
+    #[cfg(target_arch = "wasm32")]
+    if let Some(location) = web_sys::window().map(|window| window.location()) {
+        if let (Ok(origin), Ok(pathname)) = (location.origin(), location.pathname()) {
+            return origin + &pathname;
+        }
+    }
+    PAGES_URL.to_owned()
+}
+
+/// The fragment of the page's URL, including the `#`. Always empty when running natively.
+pub fn location_fragment() -> String {
+    #[cfg(target_arch = "wasm32")]
+    if let Some(window) = web_sys::window() {
+        return window.location().hash().unwrap_or_default();
+    }
+    String::new()
+}
+
+/// Shows the link in the address bar (on the web), without adding a history entry.
+pub fn set_location(link: &str) {
+    #[cfg(target_arch = "wasm32")]
+    if let Some(history) = web_sys::window().and_then(|window| window.history().ok()) {
+        history
+            .replace_state_with_url(&wasm_bindgen::JsValue::NULL, "", Some(link))
+            .ok();
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = link;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let state = serde_json::json!({"json_body": "{\"a\": 1692694500}", "anchor": 5});
+        let link = encode(PAGES_URL, &state);
+        let fragment = link.strip_prefix(PAGES_URL).unwrap();
+        assert_eq!(decode(fragment), Some(Ok(state)));
+        assert_eq!(decode::<serde_json::Value>("#other"), None);
+        assert!(matches!(
+            decode::<serde_json::Value>("#state=garbage"),
+            Some(Err(_))
+        ));
+    }
+}
@@ -0,0 +1,192 @@
+use chrono::{Datelike, NaiveDate, Utc};
+
+use crate::datetime::{DurationStyle, EpochFamily, Tz, EPOCH_FAMILIES};
+use crate::rules::{skip_invalid_rules, skip_unparsable, PathPattern, Rule};
+
+/// The detection and display settings, i.e. everything that decides how a given payload reads.
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Settings {
+    pub min_year: i32,
+    pub max_year: i32,
+    pub fmt: String,
+    pub time_zone: Tz,
+    /// The epoch families to look for. They are tried in `EPOCH_FAMILIES` order.
+    #[serde(deserialize_with = "skip_unknown_families")]
+    pub families: Vec<EpochFamily>,
+    /// The formats strings are parsed with, in order (see `parse_formatted`).
+    pub formats: Vec<String>,
+    /// Hits scored below this are hidden.
+    pub min_confidence: f32,
+    /// The user's corrections, applied before the heuristics.
+    #[serde(deserialize_with = "skip_invalid_rules")]
+    pub rules: Vec<Rule>,
+    /// Only show hits inside these paths (all of them, if empty).
+    #[serde(deserialize_with = "skip_unparsable")]
+    pub include: Vec<PathPattern>,
+    /// Never show hits inside these paths.
+    #[serde(deserialize_with = "skip_unparsable")]
+    pub exclude: Vec<PathPattern>,
+    /// How durations and relative times are written.
+    pub duration_style: DurationStyle,
+}
+
+/// Deserializes family names, dropping the ones that aren't registered (any more).
+fn skip_unknown_families<'de, D>(deserializer: D) -> Result<Vec<EpochFamily>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let names: Vec<String> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(names
+        .iter()
+        .filter_map(|name| EpochFamily::by_name(name))
+        .collect())
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let current_year = Utc::now().year();
+        Self {
+            min_year: current_year - 2,
+            max_year: current_year + 3,
+            fmt: "%Y-%m-%d %H:%M:%S".to_owned(),
+            time_zone: Tz::Utc,
+            // Excel serials are small numbers that collide with ids and counts, so they're opt-in.
+            families: EPOCH_FAMILIES
+                .iter()
+                .filter(|family| !family.name.starts_with("Excel"))
+                .copied()
+                .collect(),
+            formats: vec!["rfc3339".to_owned()],
+            min_confidence: 0.0,
+            rules: vec![],
+            include: vec![],
+            exclude: vec![],
+            duration_style: DurationStyle::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// The enabled families, in the order they should be tried.
+    pub fn enabled_families(&self) -> Vec<EpochFamily> {
+        EPOCH_FAMILIES
+            .iter()
+            .filter(|family| self.families.contains(family))
+            .copied()
+            .collect()
+    }
+}
+
+/// Checks a year range from outside the app (a profile, a link, the command line): both years
+/// must be ones chrono can represent, and the range mustn't be empty.
+pub fn check_years(min_year: i32, max_year: i32) -> Result<(), String> {
+    let years = NaiveDate::MIN.year()..=NaiveDate::MAX.year();
+    for year in [min_year, max_year] {
+        if !years.contains(&year) {
+            return Err(format!(
+                "year {} is outside {}..{}",
+                year,
+                years.start(),
+                years.end()
+            ));
+        }
+    }
+    if min_year > max_year {
+        return Err(format!(
+            "min year {} is after max year {}",
+            min_year, max_year
+        ));
+    }
+    Ok(())
+}
+
+/// Bump when a change to `Settings` can't be read by older versions.
+pub const PROFILE_VERSION: u32 = 1;
+
+/// Named settings, shared as a JSON file so a team reads payloads the same way.
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Profile {
+    pub version: u32,
+    pub name: String,
+    pub settings: Settings,
+}
+
+impl Profile {
+    pub fn new(name: String, settings: Settings) -> Self {
+        Profile {
+            version: PROFILE_VERSION,
+            name,
+            settings,
+        }
+    }
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let profile: Profile = serde_json::from_str(json).map_err(|err| err.to_string())?;
+        if profile.version > PROFILE_VERSION {
+            return Err(format!(
+                "profile version {} is newer than the supported version {}",
+                profile.version, PROFILE_VERSION
+            ));
+        }
+        check_years(profile.settings.min_year, profile.settings.max_year)?;
+        Ok(profile)
+    }
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let json =
+            std::fs::read_to_string(path).map_err(|err| format!("can't read {}: {}", path, err))?;
+        Self::from_json(&json).map_err(|err| format!("bad profile {}: {}", path, err))
+    }
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("profiles are always serializable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::RuleAction;
+
+    #[test]
+    fn test_round_trip() {
+        let mut settings = Settings {
+            time_zone: Tz::Fixed(-5 * 3600),
+            ..Default::default()
+        };
+        settings.rules.push(Rule {
+            pattern: "events[*].t".parse().unwrap(),
+            action: RuleAction::Family(EpochFamily::by_name("Unix milliseconds").unwrap()),
+        });
+        settings.exclude.push("metadata".parse().unwrap());
+        let profile = Profile::new("billing".to_owned(), settings);
+        assert_eq!(Profile::from_json(&profile.to_json()), Ok(profile));
+
+        let partial = r#"{"version": 1, "name": "short", "settings": {"min_year": 2000}}"#;
+        let partial = Profile::from_json(partial).unwrap();
+        assert_eq!(partial.settings.min_year, 2000);
+        assert_eq!(partial.settings.fmt, Settings::default().fmt);
+
+        let bad_rule = r#"{"version": 1, "name": "old", "settings": {"rules": [
+            {"pattern": "a[", "action": "NotATimestamp"},
+            {"pattern": "b", "action": "NotATimestamp"},
+            {"pattern": "d", "action": {"Family": "Unix eons"}}],
+            "exclude": ["c", "e["], "families": ["Unix seconds", "Unix eons"]}}"#;
+        let settings = Profile::from_json(bad_rule).unwrap().settings;
+        assert_eq!(settings.rules.len(), 1);
+        assert_eq!(settings.exclude, ["c".parse().unwrap()]);
+        assert_eq!(settings.families, [EPOCH_FAMILIES[0]]);
+
+        for years in [
+            r#""min_year": 2030, "max_year": 2020"#,
+            r#""max_year": 2147483647"#,
+        ] {
+            let profile = format!(
+                r#"{{"version": 1, "name": "y", "settings": {{{}}}}}"#,
+                years
+            );
+            assert!(Profile::from_json(&profile).is_err(), "{}", years);
+        }
+
+        let future = r#"{"version": 2, "name": "new", "settings": {}}"#;
+        assert!(Profile::from_json(future).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+use std::ops::Range;
+
+use chrono::Duration;
+use serde_json::Value;
+
+use crate::datetime::{format_like, Timestamp};
+use crate::json_crawl::{Hit, Interpretation, JsonPath, JsonPathPart};
+use crate::scanner::Scanner;
+use crate::spans::find_span;
+
+/// `ts` written the way the hit's value `original` is: in the same epoch family, as a number or a
+/// string, in the same date format, or as a protobuf Timestamp with `seconds` the same type.
+/// `None` if it can't be, e.g. if `ts` is out of the format's range.
+pub fn encode_like(hit: &Hit, original: &Value, ts: Timestamp) -> Option<Value> {
+    match (&hit.interpretation, original) {
+        (Interpretation::Epoch(family), Value::Number(number)) => {
+            let mut value = family.value_of(ts)?;
+            // Keep floats floats, even when the new value is whole.
+            if number.is_f64() && !value.contains('.') {
+                value.push_str(".0");
+            }
+            Some(Value::Number(value.parse().ok()?))
+        }
+        (Interpretation::Epoch(family), Value::String(_)) => {
+            Some(Value::String(family.value_of(ts)?))
+        }
+        (Interpretation::Format(format), Value::String(s)) => {
+            Some(Value::String(format_like(ts, s, format)?))
+        }
+        (Interpretation::ProtoTimestamp, Value::Object(object)) => {
+            let mut object = object.clone();
+            let seconds = match object.get("seconds")? {
+                Value::String(_) => Value::String(ts.secs.to_string()),
+                _ => Value::from(ts.secs),
+            };
+            object.insert("seconds".to_owned(), seconds);
+            if ts.nanos != 0 || object.contains_key("nanos") {
+                object.insert("nanos".to_owned(), Value::from(ts.nanos));
+            }
+            Some(Value::Object(object))
+        }
+        _ => None,
+    }
+}
+
+/// The replacements that write `ts` over the hit's value, at `span` in `text`, the way it was
+/// written (see `encode_like`). Only a protobuf Timestamp's `seconds` and `nanos` are replaced,
+/// unless it needs a `nanos` it doesn't have.
+fn hit_edits(
+    text: &str,
+    span: Range<usize>,
+    hit: &Hit,
+    ts: Timestamp,
+) -> Result<Vec<(Range<usize>, String)>, String> {
+    let to_string = |value: &Value| serde_json::to_string(value).map_err(|err| err.to_string());
+    let original: Value =
+        serde_json::from_str(&text[span.clone()]).map_err(|err| err.to_string())?;
+    let new_value = encode_like(hit, &original, ts).ok_or(format!(
+        "{} can't be written like {}",
+        ts,
+        &text[span.clone()]
+    ))?;
+    let (Value::Object(old_fields), Value::Object(new_fields)) = (&original, &new_value) else {
+        return Ok(vec![(span, to_string(&new_value)?)]);
+    };
+    let mut edits = vec![];
+    for (key, value) in new_fields {
+        if old_fields.get(key) == Some(value) {
+            continue;
+        }
+        let path = JsonPath(vec![JsonPathPart::Field(key.clone())]);
+        match find_span(&text[span.clone()], &path) {
+            Some(field) => edits.push((
+                span.start + field.start..span.start + field.end,
+                to_string(value)?,
+            )),
+            None => return Ok(vec![(span, to_string(&new_value)?)]),
+        }
+    }
+    Ok(edits)
+}
+
+/// `text` with each range replaced. The ranges don't overlap.
+fn apply_edits(text: &str, mut edits: Vec<(Range<usize>, String)>) -> String {
+    edits.sort_by_key(|(range, _)| range.start);
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    for (range, new_text) in edits {
+        out.push_str(&text[pos..range.start]);
+        out.push_str(&new_text);
+        pos = range.end;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+/// Replaces each hit's value in `text`, the JSON they were found in and located in (see
+/// `locate_hits`), with the time `new_ts` gives it, written like the original (see
+/// `encode_like`). Only those values change: the text's formatting and key order are kept.
+/// Nothing is changed if any of them can't be written.
+pub fn rewrite_hits(
+    text: &str,
+    hits: &[Hit],
+    new_ts: impl Fn(&Hit) -> Option<Timestamp>,
+) -> Result<String, String> {
+    let mut edits = vec![];
+    for hit in hits {
+        let span = hit
+            .span
+            .as_ref()
+            .ok_or(format!("{} isn't in the text as is", hit.path))?;
+        let ts = new_ts(hit).ok_or(format!("can't rewrite {}", hit.path))?;
+        edits.extend(hit_edits(text, span.range.clone(), hit, ts)?);
+    }
+    Ok(apply_edits(text, edits))
+}
+
+/// Rewrites the hit's value in `text` as `ts`, written like it was (see `encode_like`), and
+/// leaves the rest of the text as it is: its formatting and key order are kept.
+pub fn edit_hit(text: &str, hit: &Hit, ts: Timestamp) -> Result<String, String> {
+    let span = find_span(text, &hit.path).ok_or(format!(
+        "{} isn't in the text as is, e.g. it's inside a JWT",
+        hit.path
+    ))?;
+    Ok(apply_edits(text, hit_edits(text, span, hit, ts)?))
+}
+
+/// How to move a document's timestamps.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shift {
+    By(Duration),
+    /// Move them all so that `from`, or the earliest of them if it's `None`, lands on `to`.
+    Rebase {
+        from: Option<Timestamp>,
+        to: Timestamp,
+    },
+}
+
+impl Shift {
+    /// How far the hits move. `None` when rebasing the earliest of no hits, or when it's too far
+    /// for a `Duration`.
+    pub fn offset(self, hits: &[Hit]) -> Option<Duration> {
+        match self {
+            Shift::By(duration) => Some(duration),
+            Shift::Rebase { from, to } => {
+                let from = from.or_else(|| hits.iter().map(|hit| hit.ts).min())?;
+                to.since(from)
+            }
+        }
+    }
+}
+
+/// Shifts every timestamp `scanner` finds in a JSON document or NDJSON stream, keeping each
+/// value's unit, type and format (see `encode_like`), and returns the new text (see
+/// `rewrite_hits`). JWTs are left alone, since they'd need re-signing.
+pub fn shift_text(text: &str, scanner: &Scanner, shift: Shift) -> Result<String, String> {
+    let hits = scanner.scan_str(text).map_err(|err| err.to_string())?;
+    if hits.is_empty() {
+        return Ok(text.to_owned());
+    }
+    let offset = shift.offset(&hits).ok_or("the shift is too far")?;
+    rewrite_hits(text, &hits, |hit| hit.ts.checked_add(offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime::EpochFamily;
+
+    #[test]
+    fn test_shift_text() {
+        let scanner = Scanner::new()
+            .years(2020, 2030)
+            .families([EpochFamily::by_name("Unix seconds").unwrap()])
+            .formats(["rfc3339", "%d/%m/%Y"]);
+        let text = r#"{"z": 1692694500, "b": "1692694500.5", "c": "2023-08-22T10:55:00.000+02:00",
+            "d": "22/08/2023", "e": { "nanos": 5, "seconds": "1692694500" }, "f": 1692694500.5,
+            "id": 7, "g": {"seconds": 1692694500}}"#;
+        let day = Shift::By(Duration::days(1));
+        assert_eq!(
+            shift_text(text, &scanner, day).unwrap(),
+            r#"{"z": 1692780900, "b": "1692780900.5", "c": "2023-08-23T10:55:00.000+02:00",
+            "d": "23/08/2023", "e": { "nanos": 5, "seconds": "1692780900" }, "f": 1692780900.5,
+            "id": 7, "g": {"seconds": 1692780900}}"#
+        );
+        // A `nanos` it didn't have is added.
+        assert_eq!(
+            shift_text(
+                "{\"g\": {\"seconds\": 1692694500}}",
+                &scanner,
+                Shift::By(Duration::milliseconds(5))
+            ),
+            Ok("{\"g\": {\"nanos\":5000000,\"seconds\":1692694500}}".to_owned())
+        );
+
+        let stream = "{\"t\": 1692694500}\n{\"t\": 1692694600}\n";
+        let rebase = Shift::Rebase {
+            from: None,
+            to: Timestamp::from_secs(1700000000),
+        };
+        assert_eq!(
+            shift_text(stream, &scanner, rebase).unwrap(),
+            "{\"t\": 1700000000}\n{\"t\": 1700000100}\n"
+        );
+        assert_eq!(
+            shift_text(
+                "[1692694500.5]",
+                &scanner,
+                Shift::By(Duration::milliseconds(500))
+            ),
+            Ok("[1692694501.0]".to_owned())
+        );
+        let far = Shift::By(Duration::days(365 * 300_000));
+        assert!(shift_text(r#"{"d": "22/08/2023"}"#, &scanner, far).is_err());
+
+        let text = "{\n  \"z\": 1,\n  \"at\": \"2023-08-22T08:55:00Z\" }";
+        let hit = &scanner.scan_str(text).unwrap()[0];
+        let edited = edit_hit(text, hit, Timestamp::from_secs(1700000000)).unwrap();
+        assert_eq!(
+            edited,
+            "{\n  \"z\": 1,\n  \"at\": \"2023-11-14T22:13:20Z\" }"
+        );
+    }
+}
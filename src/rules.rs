@@ -0,0 +1,304 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::datetime::EpochFamily;
+use crate::json_crawl::{JsonPath, JsonPathPart};
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum PatternPart {
+    Field(String),
+    Index(usize),
+    /// `*`, any object key.
+    AnyField,
+    /// `[*]`, any array index.
+    AnyIndex,
+}
+
+/// A `JsonPath` that may contain wildcards, e.g. `events[*].created_at`. Keys that would read
+/// as something else, like `a.b` or `*`, are written quoted in brackets: `["a.b"]`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PathPattern(pub Vec<PatternPart>);
+
+impl PathPattern {
+    /// Matches exactly this path.
+    pub fn exact(path: &JsonPath) -> Self {
+        PathPattern(
+            path.0
+                .iter()
+                .map(|part| match part {
+                    JsonPathPart::Field(field) => PatternPart::Field(field.clone()),
+                    JsonPathPart::Index(index) => PatternPart::Index(*index),
+                })
+                .collect(),
+        )
+    }
+    /// Matches this path, with every array index replaced by `[*]`.
+    pub fn any_index(path: &JsonPath) -> Self {
+        PathPattern(
+            path.0
+                .iter()
+                .map(|part| match part {
+                    JsonPathPart::Field(field) => PatternPart::Field(field.clone()),
+                    JsonPathPart::Index(_) => PatternPart::AnyIndex,
+                })
+                .collect(),
+        )
+    }
+    pub fn matches(&self, path: &JsonPath) -> bool {
+        self.0.len() == path.0.len() && self.matches_prefix_of(path)
+    }
+    /// Whether the path is, or is inside, a path matching the pattern.
+    pub fn matches_prefix_of(&self, path: &JsonPath) -> bool {
+        self.0.len() <= path.0.len()
+            && self
+                .0
+                .iter()
+                .zip(path.0.iter())
+                .all(|(pattern, part)| match (pattern, part) {
+                    (PatternPart::Field(a), JsonPathPart::Field(b)) => a == b,
+                    (PatternPart::Index(a), JsonPathPart::Index(b)) => a == b,
+                    (PatternPart::AnyField, JsonPathPart::Field(_)) => true,
+                    (PatternPart::AnyIndex, JsonPathPart::Index(_)) => true,
+                    _ => false,
+                })
+    }
+}
+
+/// A path passes if it's inside one of the `include` patterns (or there are none),
+/// and not inside any of the `exclude` patterns.
+pub fn path_allowed(include: &[PathPattern], exclude: &[PathPattern], path: &JsonPath) -> bool {
+    (include.is_empty()
+        || include
+            .iter()
+            .any(|pattern| pattern.matches_prefix_of(path)))
+        && !exclude
+            .iter()
+            .any(|pattern| pattern.matches_prefix_of(path))
+}
+
+/// Whether a key must be quoted to read back as itself.
+fn needs_quotes(field: &str) -> bool {
+    field.is_empty() || field == "*" || field.contains(['.', '[', ']', '"'])
+}
+
+impl Display for PathPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, part) in self.0.iter().enumerate() {
+            match part {
+                PatternPart::Field(field) if needs_quotes(field) => {
+                    let quoted = serde_json::to_string(field).map_err(|_| std::fmt::Error)?;
+                    write!(f, "[{}]", quoted)?;
+                    continue;
+                }
+                PatternPart::Field(_) | PatternPart::AnyField if i > 0 => write!(f, ".")?,
+                _ => {}
+            }
+            match part {
+                PatternPart::Field(field) => write!(f, "{}", field)?,
+                PatternPart::Index(index) => write!(f, "[{}]", index)?,
+                PatternPart::AnyField => write!(f, "*")?,
+                PatternPart::AnyIndex => write!(f, "[*]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PathPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = vec![];
+        let mut rest = s;
+        while !rest.is_empty() {
+            if let Some(quoted) = rest.strip_prefix("[\"") {
+                // The closing quote is the first one that isn't escaped.
+                let mut end = 0;
+                let bytes = quoted.as_bytes();
+                while end < bytes.len() && bytes[end] != b'"' {
+                    end += if bytes[end] == b'\\' { 2 } else { 1 };
+                }
+                let after = quoted
+                    .get(end + 1..)
+                    .and_then(|after| after.strip_prefix(']'))
+                    .ok_or_else(|| format!("unterminated quoted key in {:?}", s))?;
+                let field = serde_json::from_str(&rest[1..end + 3])
+                    .map_err(|err| format!("bad quoted key in {:?}: {}", s, err))?;
+                parts.push(PatternPart::Field(field));
+                rest = after;
+            } else if let Some(after_bracket) = rest.strip_prefix('[') {
+                let (index, after) = after_bracket
+                    .split_once(']')
+                    .ok_or_else(|| format!("missing ']' in {:?}", s))?;
+                parts.push(match index {
+                    "*" => PatternPart::AnyIndex,
+                    index => PatternPart::Index(
+                        index
+                            .parse()
+                            .map_err(|_| format!("bad array index {:?} in {:?}", index, s))?,
+                    ),
+                });
+                rest = after;
+            } else {
+                let field_start = match rest.strip_prefix('.') {
+                    Some(after_dot) if !parts.is_empty() => after_dot,
+                    _ => rest,
+                };
+                let end = field_start.find(['.', '[']).unwrap_or(field_start.len());
+                let (field, after) = field_start.split_at(end);
+                parts.push(match field {
+                    "" => return Err(format!("empty key in {:?}", s)),
+                    "*" => PatternPart::AnyField,
+                    field => PatternPart::Field(field.to_owned()),
+                });
+                rest = after;
+            }
+        }
+        Ok(PathPattern(parts))
+    }
+}
+
+impl serde::Serialize for PathPattern {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for PathPattern {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <String as serde::Deserialize>::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+pub enum RuleAction {
+    Family(EpochFamily),
+    NotATimestamp,
+}
+
+impl Display for RuleAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleAction::Family(family) => write!(f, "{}", family.name),
+            RuleAction::NotATimestamp => write!(f, "Not a timestamp"),
+        }
+    }
+}
+
+/// Deserializes a list of strings, e.g. saved path patterns, dropping the ones that don't parse
+/// (e.g. written by another version), so one bad item doesn't lose the rest of the saved state.
+pub fn skip_unparsable<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: FromStr,
+{
+    let items: Vec<String> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(items.iter().filter_map(|item| item.parse().ok()).collect())
+}
+
+/// Like `skip_unparsable`, for rules: those with a bad pattern or an unknown family are dropped.
+pub fn skip_invalid_rules<'de, D>(deserializer: D) -> Result<Vec<Rule>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    // `Rule` with its strings not parsed yet.
+    #[derive(serde::Deserialize)]
+    enum Action {
+        Family(String),
+        NotATimestamp,
+    }
+    #[derive(serde::Deserialize)]
+    struct Saved {
+        pattern: String,
+        action: Action,
+    }
+    let rules: Vec<Saved> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(rules
+        .into_iter()
+        .filter_map(|rule| {
+            Some(Rule {
+                pattern: rule.pattern.parse().ok()?,
+                action: match rule.action {
+                    Action::Family(name) => RuleAction::Family(EpochFamily::by_name(&name)?),
+                    Action::NotATimestamp => RuleAction::NotATimestamp,
+                },
+            })
+        })
+        .collect())
+}
+
+/// A user's correction of the detection heuristics, for all paths matching a pattern.
+#[derive(Clone, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Rule {
+    pub pattern: PathPattern,
+    pub action: RuleAction,
+}
+
+/// The first rule that applies to the path, if any.
+pub fn find_rule<'a>(rules: &'a [Rule], path: &JsonPath) -> Option<&'a Rule> {
+    rules.iter().find(|rule| rule.pattern.matches(path))
+}
+
+/// Adds a rule ahead of the existing ones, replacing any rule for the same pattern.
+pub fn add_rule(rules: &mut Vec<Rule>, rule: Rule) {
+    rules.retain(|existing| existing.pattern != rule.pattern);
+    rules.insert(0, rule);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern() {
+        let path = JsonPath(vec![
+            JsonPathPart::Field("events".to_owned()),
+            JsonPathPart::Index(3),
+            JsonPathPart::Field("created_at".to_owned()),
+        ]);
+        assert_eq!(
+            PathPattern::exact(&path).to_string(),
+            "events[3].created_at"
+        );
+        let pattern = PathPattern::any_index(&path);
+        assert_eq!(pattern.to_string(), "events[*].created_at");
+        for s in ["events[*].created_at", "*[3].created_at", "events[3].*"] {
+            let parsed: PathPattern = s.parse().unwrap();
+            assert_eq!(parsed.to_string(), s);
+            assert!(parsed.matches(&path), "{}", s);
+        }
+        for s in ["events[2].created_at", "events[*]", "events.created_at"] {
+            let parsed: PathPattern = s.parse().unwrap();
+            assert!(!parsed.matches(&path), "{}", s);
+        }
+        for s in ["events[x]", "events[3", "events..a", "[\"a", "[\"a\"b"] {
+            assert!(s.parse::<PathPattern>().is_err(), "{}", s);
+        }
+
+        let quoted = PathPattern(vec![
+            PatternPart::Field("a.b".to_owned()),
+            PatternPart::Field("*".to_owned()),
+            PatternPart::AnyField,
+            PatternPart::Field("x[0]\"".to_owned()),
+            PatternPart::Field(String::new()),
+            PatternPart::Index(1),
+        ]);
+        assert_eq!(quoted.to_string(), r#"["a.b"]["*"].*["x[0]\""][""][1]"#);
+        assert_eq!(quoted.to_string().parse(), Ok(quoted));
+        let dotted = PathPattern(vec![PatternPart::Field("a.b".to_owned())]);
+        assert!(!dotted.matches(&JsonPath(vec![
+            JsonPathPart::Field("a".to_owned()),
+            JsonPathPart::Field("b".to_owned()),
+        ])));
+
+        let events: PathPattern = "events".parse().unwrap();
+        let other: PathPattern = "other".parse().unwrap();
+        assert!(path_allowed(&[], &[], &path));
+        assert!(path_allowed(&[events.clone()], &[], &path));
+        assert!(!path_allowed(&[other.clone()], &[], &path));
+        assert!(!path_allowed(&[], &[events.clone()], &path));
+        assert!(path_allowed(&[], &[other], &path));
+    }
+}
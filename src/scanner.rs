@@ -0,0 +1,143 @@
+use chrono::Duration;
+use serde_json::Value;
+
+use crate::confidence::score_hits;
+use crate::datetime::{year_to_ts, EpochFamily};
+use crate::json_crawl::{crawl_durations, crawl_json, parse_json_stream, Hit, JsonPath};
+use crate::profile::Settings;
+use crate::rules::{path_allowed, PathPattern, Rule};
+use crate::spans::locate_hits;
+
+/// Finds timestamps in JSON documents.
+///
+/// ```
+/// use json_unix_time::{EpochFamily, Scanner};
+///
+/// let scanner = Scanner::new()
+///     .years(2020, 2030)
+///     .families([EpochFamily::by_name("Unix milliseconds").unwrap()]);
+/// let hits = scanner.scan_str(r#"{"created_at": 1692694500123}"#).unwrap();
+/// assert_eq!(hits[0].path.to_string(), "created_at");
+/// assert_eq!(hits[0].ts.secs, 1692694500);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Scanner {
+    min_year: i32,
+    max_year: i32,
+    families: Vec<EpochFamily>,
+    formats: Vec<String>,
+    rules: Vec<Rule>,
+    include: Vec<PathPattern>,
+    exclude: Vec<PathPattern>,
+    min_confidence: f32,
+}
+
+impl Default for Scanner {
+    fn default() -> Self {
+        Self::from_settings(&Settings::default())
+    }
+}
+
+impl Scanner {
+    /// A scanner with the same defaults as the app.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn from_settings(settings: &Settings) -> Self {
+        Scanner {
+            min_year: settings.min_year,
+            max_year: settings.max_year,
+            families: settings.enabled_families(),
+            formats: settings.formats.clone(),
+            rules: settings.rules.clone(),
+            include: settings.include.clone(),
+            exclude: settings.exclude.clone(),
+            min_confidence: settings.min_confidence,
+        }
+    }
+    /// Only report timestamps from the start of `min_year` to the end of `max_year`.
+    pub fn years(mut self, min_year: i32, max_year: i32) -> Self {
+        self.min_year = min_year;
+        self.max_year = max_year;
+        self
+    }
+    /// The epoch families to try numbers against, in order.
+    pub fn families(mut self, families: impl IntoIterator<Item = EpochFamily>) -> Self {
+        self.families = families.into_iter().collect();
+        self
+    }
+    /// The formats to try strings against, in order (see `parse_formatted`).
+    pub fn formats(mut self, formats: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.formats = formats.into_iter().map(Into::into).collect();
+        self
+    }
+    pub fn rules(mut self, rules: impl IntoIterator<Item = Rule>) -> Self {
+        self.rules = rules.into_iter().collect();
+        self
+    }
+    /// Only report hits inside paths matching one of the included patterns.
+    pub fn include(mut self, pattern: PathPattern) -> Self {
+        self.include.push(pattern);
+        self
+    }
+    pub fn exclude(mut self, pattern: PathPattern) -> Self {
+        self.exclude.push(pattern);
+        self
+    }
+    pub fn min_confidence(mut self, min_confidence: f32) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// The timestamps in `value`, in document order.
+    pub fn scan(&self, value: &Value) -> Vec<Hit> {
+        let min_ts = year_to_ts(self.min_year).unwrap_or(i64::MIN);
+        let max_ts = self
+            .max_year
+            .checked_add(1)
+            .and_then(year_to_ts)
+            .unwrap_or(i64::MAX);
+        let predicate = |ts| (ts >= min_ts) && (ts <= max_ts);
+        let mut hits = vec![];
+        crawl_json(
+            value,
+            JsonPath::new(),
+            &self.families,
+            &self.formats,
+            &self.rules,
+            &predicate,
+            &mut hits,
+        );
+        score_hits(value, &mut hits);
+        hits.retain(|hit| hit.confidence >= self.min_confidence && self.allows(&hit.path));
+        hits
+    }
+    /// Scans a JSON document, or a stream of them (e.g. NDJSON) as an array. The hits know where
+    /// they are in the text.
+    pub fn scan_str(&self, json: &str) -> Result<Vec<Hit>, serde_json::Error> {
+        let mut hits = self.scan(&parse_json_stream(json)?);
+        locate_hits(json, &mut hits);
+        Ok(hits)
+    }
+    /// The protobuf durations (e.g. "3.5s") in `value`, in document order.
+    pub fn durations(&self, value: &Value) -> Vec<(JsonPath, Duration)> {
+        let mut durations = vec![];
+        crawl_durations(value, JsonPath::new(), &mut durations);
+        durations.retain(|(path, _)| self.allows(path));
+        durations
+    }
+    fn allows(&self, path: &JsonPath) -> bool {
+        path_allowed(&self.include, &self.exclude, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_year_range() {
+        let value = serde_json::json!({"a": 1692694500});
+        assert_eq!(Scanner::new().years(2020, i32::MAX).scan(&value).len(), 1);
+    }
+}
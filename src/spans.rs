@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::json_crawl::{Hit, JsonPath, JsonPathPart};
+
+/// Where a value is in the text it was parsed from.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SourceSpan {
+    /// In bytes.
+    pub range: Range<usize>,
+    /// Where it starts, from 1. The column counts characters.
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A quoted key's text. Most keys have no escapes, and skip serde.
+fn unquote(key: &str) -> Option<String> {
+    match key.contains('\\') {
+        true => serde_json::from_str(key).ok(),
+        false => key.get(1..key.len() - 1).map(str::to_owned),
+    }
+}
+
+/// Walks JSON text that's known to parse, without building values.
+struct Cursor<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(text: &'a str) -> Self {
+        Cursor { text, pos: 0 }
+    }
+    fn at(mut self, pos: usize) -> Self {
+        self.pos = pos;
+        self
+    }
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+    /// Skips whitespace, then `byte` if it's next.
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        let found = self.peek() == Some(byte);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+    /// A string's span, quotes included. The cursor is on the opening quote.
+    fn string(&mut self) -> Option<Range<usize>> {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.peek()? {
+                b'"' => break,
+                b'\\' => self.pos += 2,
+                _ => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+        Some(start..self.pos)
+    }
+    /// Skips the next value and returns its span.
+    fn value(&mut self) -> Option<Range<usize>> {
+        self.skip_whitespace();
+        let start = self.pos;
+        match self.peek()? {
+            b'"' => return self.string(),
+            b'{' => {
+                self.members::<()>(|_, cursor| cursor.value().map(|_| None))?;
+            }
+            b'[' => {
+                self.items::<()>(|_, cursor| cursor.value().map(|_| None))?;
+            }
+            _ => {
+                let len = self.text[start..]
+                    .find([',', ']', '}', ' ', '\t', '\n', '\r'])
+                    .unwrap_or(self.text.len() - start);
+                self.pos += len;
+            }
+        };
+        Some(start..self.pos)
+    }
+    /// Calls `member` with each key (still quoted) of the object the cursor is on, with the cursor
+    /// on the member's value, until it returns something. `member` must consume the value.
+    fn members<T>(
+        &mut self,
+        mut member: impl FnMut(&str, &mut Self) -> Option<Option<T>>,
+    ) -> Option<Option<T>> {
+        self.pos += 1;
+        if self.eat(b'}') {
+            return Some(None);
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            if !self.eat(b':') {
+                return None;
+            }
+            if let Some(found) = member(&self.text[key], self)? {
+                return Some(Some(found));
+            }
+            if self.eat(b'}') {
+                return Some(None);
+            }
+            if !self.eat(b',') {
+                return None;
+            }
+        }
+    }
+    /// Like `members`, for the items of the array the cursor is on, with their indices.
+    fn items<T>(
+        &mut self,
+        mut item: impl FnMut(usize, &mut Self) -> Option<Option<T>>,
+    ) -> Option<Option<T>> {
+        self.pos += 1;
+        if self.eat(b']') {
+            return Some(None);
+        }
+        for index in 0.. {
+            if let Some(found) = item(index, self)? {
+                return Some(Some(found));
+            }
+            if self.eat(b']') {
+                break;
+            }
+            if !self.eat(b',') {
+                return None;
+            }
+        }
+        Some(None)
+    }
+    /// Skips the next value like `value`, calling `visit` with each value inside it, itself
+    /// last, and their paths (`path` extended).
+    fn walk(
+        &mut self,
+        path: &mut JsonPath,
+        visit: &mut impl FnMut(&JsonPath, Range<usize>),
+    ) -> Option<Range<usize>> {
+        self.skip_whitespace();
+        let start = self.pos;
+        match self.peek()? {
+            b'{' => {
+                self.members::<()>(|key, cursor| {
+                    path.0.push(JsonPathPart::Field(unquote(key)?));
+                    let walked = cursor.walk(path, visit);
+                    path.0.pop();
+                    walked.map(|_| None)
+                })?;
+            }
+            b'[' => {
+                self.items::<()>(|index, cursor| {
+                    path.0.push(JsonPathPart::Index(index));
+                    let walked = cursor.walk(path, visit);
+                    path.0.pop();
+                    walked.map(|_| None)
+                })?;
+            }
+            _ => {
+                self.value()?;
+            }
+        }
+        let span = start..self.pos;
+        visit(path, span.clone());
+        Some(span)
+    }
+    /// The span of the value at `path` inside the value the cursor is before.
+    fn find(&mut self, path: &[JsonPathPart]) -> Option<Range<usize>> {
+        let Some((part, rest)) = path.split_first() else {
+            return self.value();
+        };
+        self.skip_whitespace();
+        let found = match (part, self.peek()?) {
+            (JsonPathPart::Field(field), b'{') => {
+                // Like serde_json, the last of duplicate keys wins, so all of them are looked at.
+                let mut last = None;
+                self.members::<()>(|key, cursor| {
+                    if unquote(key)? == *field {
+                        last = Cursor::new(cursor.text).at(cursor.pos).find(rest);
+                    }
+                    cursor.value().map(|_| None)
+                })?;
+                Some(last)
+            }
+            (JsonPathPart::Index(index), b'[') => self.items(|i, cursor| match i == *index {
+                true => cursor.find(rest).map(Some),
+                false => cursor.value().map(|_| None),
+            }),
+            _ => None,
+        };
+        found?
+    }
+}
+
+/// The spans of the documents in `text`: one for plain JSON, several for a stream.
+fn documents(text: &str) -> Option<Vec<Range<usize>>> {
+    let mut documents = vec![];
+    let mut cursor = Cursor::new(text);
+    loop {
+        cursor.skip_whitespace();
+        if cursor.peek().is_none() {
+            return Some(documents);
+        }
+        documents.push(cursor.value()?);
+    }
+}
+
+/// Calls `visit` with the path and byte range of every value in a JSON document or stream
+/// (paths as in `find_span`), children before their parents. `None` if the text isn't JSON.
+pub fn visit_spans(text: &str, mut visit: impl FnMut(&JsonPath, Range<usize>)) -> Option<()> {
+    let documents = documents(text)?;
+    let is_stream = documents.len() > 1;
+    for (i, document) in documents.into_iter().enumerate() {
+        let mut path = match is_stream {
+            true => JsonPath(vec![JsonPathPart::Index(i)]),
+            false => JsonPath::new(),
+        };
+        let mut cursor = Cursor::new(text);
+        cursor.pos = document.start;
+        cursor.walk(&mut path, &mut visit)?;
+    }
+    Some(())
+}
+
+/// Fills in where each hit is in `text`, the JSON it was found in, in one pass. Hits that aren't
+/// in it as they are (e.g. inside a JWT) are left without a span.
+pub fn locate_hits(text: &str, hits: &mut [Hit]) {
+    let by_path: HashMap<&JsonPath, usize> = hits
+        .iter()
+        .enumerate()
+        .map(|(i, hit)| (&hit.path, i))
+        .collect();
+    let mut found = vec![];
+    visit_spans(text, |path, range| {
+        if let Some(&i) = by_path.get(path) {
+            found.push((i, range));
+        }
+    });
+    found.sort_by_key(|(_, range)| range.start);
+    // Lines and columns are counted in a single pass over the text, up to each span's start.
+    let (mut pos, mut line, mut column) = (0, 1, 1);
+    for (i, range) in found {
+        for byte in &text.as_bytes()[pos..range.start] {
+            match byte {
+                b'\n' => (line, column) = (line + 1, 1),
+                // Continuation bytes don't start a character.
+                byte if byte & 0xC0 != 0x80 => column += 1,
+                _ => {}
+            }
+        }
+        pos = range.start;
+        hits[i].span = Some(SourceSpan {
+            range,
+            line,
+            column,
+        });
+    }
+}
+
+/// The byte range of the value at `path` in a JSON document, or in a stream of them (whose
+/// documents are indexed like the array `parse_json_stream` makes). `None` if it isn't in the
+/// text, e.g. if it's inside a JWT, or if the text isn't JSON.
+pub fn find_span(text: &str, path: &JsonPath) -> Option<Range<usize>> {
+    let documents = documents(text)?;
+    let (document, path) = match (documents.as_slice(), path.0.as_slice()) {
+        ([document], path) => (document.clone(), path),
+        (_, [JsonPathPart::Index(index), path @ ..]) => (documents.get(*index)?.clone(), path),
+        _ => return None,
+    };
+    let mut cursor = Cursor::new(text);
+    cursor.pos = document.start;
+    cursor.find(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_span() {
+        let text = r#" {"a": [1, {"b\"": "x,}"}, 3] , "c" : -1.5e3 }"#;
+        let span = |path: &str| {
+            let path = JsonPath(
+                path.split('.')
+                    .filter(|part| !part.is_empty())
+                    .map(|part| match part.parse() {
+                        Ok(index) => JsonPathPart::Index(index),
+                        Err(_) => JsonPathPart::Field(part.to_owned()),
+                    })
+                    .collect(),
+            );
+            find_span(text, &path).map(|span| &text[span])
+        };
+        assert_eq!(span(""), Some(text.trim()));
+        assert_eq!(span("a.1.b\""), Some(r#""x,}""#));
+        assert_eq!(span("a.2"), Some("3"));
+        assert_eq!(span("c"), Some("-1.5e3"));
+        assert_eq!(span("a.3"), None);
+        assert_eq!(span("a.1.b\".x"), None);
+
+        let duplicates = r#"{"t": {"u": 1}, "t": {"u": 2}, "v": {"w": 3}, "v": 4}"#;
+        let span = |path: &[&str]| {
+            let path = JsonPath(
+                path.iter()
+                    .map(|part| JsonPathPart::Field(part.to_string()))
+                    .collect(),
+            );
+            find_span(duplicates, &path).map(|span| &duplicates[span])
+        };
+        assert_eq!(span(&["t", "u"]), Some("2"));
+        assert_eq!(span(&["v", "w"]), None);
+
+        let stream = "{\"t\": 1}\n{\"t\": 22}\n";
+        let path = JsonPath(vec![
+            JsonPathPart::Index(1),
+            JsonPathPart::Field("t".to_owned()),
+        ]);
+        assert_eq!(
+            find_span(stream, &path).map(|span| &stream[span]),
+            Some("22")
+        );
+    }
+
+    #[test]
+    fn test_locate_hits() {
+        let text = "{\n  \"née\": 1692694500, \"at\": \"2023-08-22T08:55:00Z\",\n  \"p\": {\"seconds\": 1692694500}\n}";
+        let mut hits = crate::scanner::Scanner::new()
+            .years(2020, 2030)
+            .scan_str(text)
+            .unwrap();
+        hits.sort_by_key(|hit| hit.path.to_string());
+        let spans: Vec<_> = hits
+            .iter()
+            .map(|hit| {
+                let span = hit.span.clone().unwrap();
+                (&text[span.range], span.line, span.column)
+            })
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                ("\"2023-08-22T08:55:00Z\"", 2, 28),
+                ("1692694500", 2, 10),
+                ("{\"seconds\": 1692694500}", 3, 8),
+            ]
+        );
+    }
+}
@@ -0,0 +1,134 @@
+//! The JavaScript API, built with `--features wasm`. Options and results are plain JS objects.
+
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::annotate::annotate as annotate_value;
+use crate::datetime::{timestamp_to_str, EpochFamily};
+use crate::json_crawl::{parse_json_stream, Hit, SourceKind};
+use crate::profile::Settings;
+use crate::scanner::Scanner;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TYPESCRIPT: &'static str = r#"
+/** Any subset of the app's settings, as saved in profiles. Missing fields keep the app's defaults. */
+export interface Options {
+    min_year?: number;
+    max_year?: number;
+    /** A chrono format, e.g. "%Y-%m-%d %H:%M:%S". */
+    fmt?: string;
+    /** "UTC", "Local", or an offset like "+05:30". */
+    time_zone?: string;
+    /** Epoch family names, e.g. "Unix milliseconds". */
+    families?: string[];
+    /** Formats strings are parsed with, e.g. "rfc3339" or "%d/%m/%Y". */
+    formats?: string[];
+    min_confidence?: number;
+    rules?: { pattern: string; action: { Family: string } | "NotATimestamp" }[];
+    /** Path patterns, e.g. "events[*].meta". */
+    include?: string[];
+    exclude?: string[];
+}
+
+export interface Hit {
+    /** e.g. "events[3].created_at". */
+    path: string;
+    /** The value as it appears in the JSON. */
+    raw: string;
+    /** Seconds since 1970-01-01 UTC. */
+    secs: number;
+    nanos: number;
+    /** The time, formatted with the `fmt` and `time_zone` options. */
+    time: string | null;
+    /** e.g. "Unix milliseconds", "Protobuf Timestamp" or "Format rfc3339". */
+    interpretation: string;
+    source: "number" | "string" | "object" | "jwt";
+    confidence: number;
+    pinned: boolean;
+    /** Where the value is in the JSON text: UTF-8 byte offsets, and the line and column it starts at (from 1). */
+    span: { start: number; end: number; line: number; column: number } | null;
+}
+
+/** The timestamps in a JSON document, in document order. */
+export function scan(json: string, options?: Options): Hit[];
+/** The document, with every timestamp replaced by {value, time, interpretation}. */
+export function annotate(json: string, options?: Options): any;
+/** Formats seconds since 1970-01-01 UTC. */
+export function format(secs: number, options?: Options): string;
+"#;
+
+fn settings_from_options(options: JsValue) -> Result<Settings, JsError> {
+    if options.is_undefined() || options.is_null() {
+        return Ok(Settings::default());
+    }
+    let json: String = js_sys::JSON::stringify(&options)
+        .map_err(|_| JsError::new("options must be a plain object"))?
+        .into();
+    serde_json::from_str(&json).map_err(|err| JsError::new(&format!("bad options: {}", err)))
+}
+
+fn parse_json(json: &str) -> Result<Value, JsError> {
+    parse_json_stream(json).map_err(|err| JsError::new(&format!("invalid JSON: {}", err)))
+}
+
+fn to_js(value: &Value) -> JsValue {
+    js_sys::JSON::parse(&value.to_string()).expect("serde_json output is valid JSON")
+}
+
+fn hit_to_json(hit: &Hit, settings: &Settings) -> Value {
+    json!({
+        "path": hit.path.to_string(),
+        "raw": hit.raw,
+        "secs": hit.ts.secs,
+        "nanos": hit.ts.nanos,
+        "time": timestamp_to_str(hit.ts, &settings.fmt, settings.time_zone),
+        "interpretation": hit.interpretation.to_string(),
+        "source": match hit.source {
+            SourceKind::Number => "number",
+            SourceKind::String => "string",
+            SourceKind::Object => "object",
+            SourceKind::Jwt => "jwt",
+        },
+        "confidence": hit.confidence,
+        "pinned": hit.pinned,
+        "span": hit.span.as_ref().map(|span| json!({
+            "start": span.range.start,
+            "end": span.range.end,
+            "line": span.line,
+            "column": span.column,
+        })),
+    })
+}
+
+#[wasm_bindgen(skip_typescript)]
+pub fn scan(json: &str, options: JsValue) -> Result<JsValue, JsError> {
+    let settings = settings_from_options(options)?;
+    let hits = Scanner::from_settings(&settings)
+        .scan_str(json)
+        .map_err(|err| JsError::new(&format!("invalid JSON: {}", err)))?;
+    let hits: Vec<Value> = hits.iter().map(|hit| hit_to_json(hit, &settings)).collect();
+    Ok(to_js(&Value::Array(hits)))
+}
+
+#[wasm_bindgen(skip_typescript)]
+pub fn annotate(json: &str, options: JsValue) -> Result<JsValue, JsError> {
+    let settings = settings_from_options(options)?;
+    let value = parse_json(json)?;
+    let hits = Scanner::from_settings(&settings).scan(&value);
+    Ok(to_js(&annotate_value(
+        &value,
+        &hits,
+        &settings.fmt,
+        settings.time_zone,
+    )))
+}
+
+#[wasm_bindgen(skip_typescript)]
+pub fn format(secs: f64, options: JsValue) -> Result<String, JsError> {
+    let settings = settings_from_options(options)?;
+    let unix_seconds = EpochFamily::by_name("Unix seconds").expect("a registered family");
+    unix_seconds
+        .timestamp_from_float(secs)
+        .and_then(|ts| timestamp_to_str(ts, &settings.fmt, settings.time_zone))
+        .ok_or_else(|| JsError::new(&format!("{} is out of range", secs)))
+}
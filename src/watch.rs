@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::datetime::Timestamp;
+use crate::json_crawl::{Hit, JsonPath};
+
+/// Notices when a file is rewritten or appended to, by polling its modification time and size.
+#[derive(Clone, Debug)]
+pub struct FileWatcher {
+    path: PathBuf,
+    last_seen: Option<(SystemTime, u64)>,
+}
+
+fn file_state(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+impl FileWatcher {
+    /// Starts watching from the file's current state.
+    pub fn new(path: PathBuf) -> Self {
+        let last_seen = file_state(&path);
+        FileWatcher { path, last_seen }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The file's new contents, if it changed since the last call.
+    pub fn poll(&mut self) -> Option<Result<String, String>> {
+        let state = file_state(&self.path);
+        if state.is_none() || state == self.last_seen {
+            // A missing file is probably being replaced, keep the last contents meanwhile.
+            return None;
+        }
+        self.last_seen = state;
+        Some(
+            std::fs::read_to_string(&self.path)
+                .map_err(|err| format!("can't read {}: {}", self.path.display(), err)),
+        )
+    }
+}
+
+/// Tells the hits that appeared in the last reload apart from the ones seen before it.
+#[derive(Clone, Debug, Default)]
+pub struct SeenHits {
+    /// `None` until the first load, whose hits are all considered old.
+    seen: Option<HashSet<(JsonPath, Timestamp)>>,
+    new: HashSet<(JsonPath, Timestamp)>,
+}
+
+impl SeenHits {
+    fn key(hit: &Hit) -> (JsonPath, Timestamp) {
+        (hit.path.clone(), hit.ts)
+    }
+
+    /// Records a (re)load's hits.
+    pub fn update(&mut self, hits: &[Hit]) {
+        let current: HashSet<_> = hits.iter().map(Self::key).collect();
+        self.new = match &self.seen {
+            Some(seen) => current.difference(seen).cloned().collect(),
+            None => HashSet::new(),
+        };
+        self.seen = Some(current);
+    }
+
+    /// Whether the hit appeared in the last reload.
+    pub fn is_new(&self, hit: &Hit) -> bool {
+        self.new.contains(&Self::key(hit))
+    }
+
+    pub fn new_count(&self) -> usize {
+        self.new.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+
+    #[test]
+    fn test_watch() {
+        static COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "json_unix_time_watch_{}_{}.json",
+            std::process::id(),
+            COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+        ));
+        std::fs::write(&path, "[1692694500]").unwrap();
+        let mut watcher = FileWatcher::new(path.clone());
+        assert_eq!(watcher.poll(), None);
+        std::fs::write(&path, "[1692694500, 1692694600]").unwrap();
+        let contents = watcher.poll().unwrap().unwrap();
+        assert_eq!(watcher.poll(), None);
+        std::fs::remove_file(&path).unwrap();
+
+        let scanner = Scanner::new().years(2020, 2030);
+        let mut seen = SeenHits::default();
+        let before = scanner.scan_str("[1692694500]").unwrap();
+        seen.update(&before);
+        assert!(!seen.is_new(&before[0]));
+        let after = scanner.scan_str(&contents).unwrap();
+        seen.update(&after);
+        assert!(!seen.is_new(&after[0]));
+        assert!(seen.is_new(&after[1]));
+        assert_eq!(seen.new_count(), 1);
+    }
+}